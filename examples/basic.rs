@@ -0,0 +1,28 @@
+use logger::{LogConfig, LogLevel, Logger, RollingConfig, RollingSizeOptions, RollingTimeOptions};
+
+fn main() {
+    let config = LogConfig::new()
+        .with_level(LogLevel::Debug)
+        .with_file_prefix("Example_".to_string())
+        .with_rolling_config(
+            RollingConfig::new()
+                .with_size_threshold(RollingSizeOptions::OneMB)
+                .with_time_threshold(RollingTimeOptions::Daily),
+        );
+
+    let logger = Logger::new(Some(config));
+
+    logger.debug("Starting example");
+    logger.info("Custom configuration applied!");
+    logger.warn("This is a warning");
+
+    let attempts = 3;
+    logger::info!(logger, "Finished after {attempts} attempts");
+    logger::debug!(logger, "Config prefix: {}", "Example_");
+
+    logger.log_kv(
+        LogLevel::Info,
+        "User signed in",
+        &[("user_id", 42.into()), ("name", "Jane Doe".into())],
+    );
+}
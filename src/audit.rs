@@ -0,0 +1,179 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+
+const GENESIS_PREFIX: &str = "#audit genesis ";
+const NEXT_PREFIX: &str = "#audit next ";
+
+type Hash = [u8; 32];
+
+// The running hash of an audit mode file. Each record line ends with
+// SHA-256(previous hash || line), starting from the hash on the file's genesis
+// line. When the file rotates its last entry is a `#audit next <file>` trailer,
+// chained like a record, whose hash is the next file's genesis.
+#[derive(Debug)]
+pub(crate) struct Chain {
+    head: Hash,
+}
+
+impl Chain {
+    // A new chain starts from the hash of the file name; a rotated file carries
+    // on from the previous file's trailer. Returns the genesis line to write.
+    pub(crate) fn start(path: &Path, previous: Option<Hash>) -> (Self, String) {
+        let head = previous.unwrap_or_else(|| {
+            let name = path.file_name().unwrap_or_default();
+            Sha256::digest(name.as_encoded_bytes()).into()
+        });
+        let line = format!("{}{}\n", GENESIS_PREFIX, to_hex(&head));
+        (Self { head }, line)
+    }
+
+    // The record with its hash appended. Line breaks inside it are escaped so
+    // the file stays one record per line.
+    pub(crate) fn seal(&mut self, record: &str) -> String {
+        let record = record.strip_suffix('\n').unwrap_or(record);
+        let content = record.replace('\r', "\\r").replace('\n', "\\n");
+        self.head = link(&self.head, content.as_bytes());
+        format!("{} {}\n", content, to_hex(&self.head))
+    }
+
+    // The hash of the last entry, which a reopened file starts from.
+    pub(crate) fn head(&self) -> Hash {
+        self.head
+    }
+
+    // The trailer line pointing at the next file, and the hash it starts from.
+    pub(crate) fn close(&mut self, next: &Path) -> (String, Hash) {
+        let name = next.file_name().unwrap_or_default().to_string_lossy();
+        let line = self.seal(&format!("{}{}", NEXT_PREFIX, name));
+        (line, self.head)
+    }
+}
+
+fn link(previous: &Hash, content: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(previous);
+    hasher.update(content);
+    hasher.finalize().into()
+}
+
+fn to_hex(hash: &Hash) -> String {
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// What verify_file found in an intact file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainReport {
+    pub records: u64,
+    pub genesis: String,
+    /// The hash of the last entry, which a following file starts from.
+    pub head: String,
+    /// Set when the file was closed by a rotation.
+    pub next_file: Option<PathBuf>,
+}
+
+/// Why Logger::verify_file rejected a file.
+#[derive(Debug)]
+pub enum ChainError {
+    Read {
+        path: PathBuf,
+        source: io::Error,
+    },
+    MissingGenesis,
+    /// The first line, counted from 1, whose hash doesn't follow from the lines
+    /// before it.
+    Corrupted {
+        line: u64,
+    },
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::Read { path, source } => {
+                write!(f, "Could not read {}: {}", path.display(), source)
+            }
+            ChainError::MissingGenesis => write!(f, "File doesn't start with a genesis hash"),
+            ChainError::Corrupted { line } => write!(f, "Hash chain broken at line {}", line),
+        }
+    }
+}
+
+impl std::error::Error for ChainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ChainError::Read { source, .. } => Some(source),
+            ChainError::MissingGenesis | ChainError::Corrupted { .. } => None,
+        }
+    }
+}
+
+// Recomputes the chain of an audit mode file. Works on bytes so an edit that
+// breaks UTF-8 still shows up as a broken line. Anything after a trailer, and a
+// last line cut off before its newline, counts as corruption too.
+pub(crate) fn verify_file(path: &Path) -> Result<ChainReport, ChainError> {
+    let contents = fs::read(path).map_err(|source| ChainError::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let mut lines = contents.split_inclusive(|&byte| byte == b'\n');
+    let genesis = lines
+        .next()
+        .and_then(|line| line.strip_prefix(GENESIS_PREFIX.as_bytes()))
+        .and_then(|hash| hash.strip_suffix(b"\n"))
+        .and_then(from_hex);
+    let Some(mut head) = genesis else {
+        return Err(ChainError::MissingGenesis);
+    };
+
+    let mut report = ChainReport {
+        records: 0,
+        genesis: to_hex(&head),
+        head: String::new(),
+        next_file: None,
+    };
+    for (index, line) in lines.enumerate() {
+        // Line numbers count from 1 and include the genesis line.
+        let corrupted = ChainError::Corrupted {
+            line: index as u64 + 2,
+        };
+        let entry = line
+            .strip_suffix(b"\n")
+            .filter(|_| report.next_file.is_none())
+            .and_then(|line| {
+                let split = line.iter().rposition(|&byte| byte == b' ')?;
+                Some((&line[..split], from_hex(&line[split + 1..])?))
+            });
+        let Some((content, hash)) = entry else {
+            return Err(corrupted);
+        };
+        let expected = link(&head, content);
+        if hash != expected {
+            return Err(corrupted);
+        }
+        head = expected;
+        match content.strip_prefix(NEXT_PREFIX.as_bytes()) {
+            Some(next) => {
+                let next = String::from_utf8_lossy(next);
+                report.next_file = Some(path.with_file_name(next.as_ref()));
+            }
+            None => report.records += 1,
+        }
+    }
+    report.head = to_hex(&head);
+    Ok(report)
+}
+
+fn from_hex(text: &[u8]) -> Option<Hash> {
+    if text.len() != 64 {
+        return None;
+    }
+    let mut hash = [0; 32];
+    for (byte, pair) in hash.iter_mut().zip(text.chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(hash)
+}
@@ -0,0 +1,117 @@
+use std::{
+    env,
+    ffi::OsStr,
+    fmt,
+    io::{self, IsTerminal, Write},
+};
+
+use crate::logger::LogLevel;
+
+// Mirrors records to a terminal. Colors only ever apply here, never to the file.
+pub(crate) struct Console {
+    writer: Box<dyn Write + Send>,
+    color: bool,
+}
+
+impl fmt::Debug for Console {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Console")
+            .field("color", &self.color)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Console {
+    pub(crate) fn stderr(color: bool) -> Self {
+        let no_color = env::var_os("NO_COLOR");
+        let color = use_color(color, no_color.as_deref(), io::stderr().is_terminal());
+        Self::new(Box::new(io::stderr()), color)
+    }
+
+    pub(crate) fn new(writer: Box<dyn Write + Send>, color: bool) -> Self {
+        Self { writer, color }
+    }
+
+    pub(crate) fn write_record(&mut self, level: LogLevel, record: &str) -> io::Result<()> {
+        let code = if self.color { color_code(level) } else { None };
+        match code {
+            Some(code) => {
+                let line = record.strip_suffix('\n').unwrap_or(record);
+                writeln!(self.writer, "{}{}\x1b[0m", code, line)
+            }
+            None => self.writer.write_all(record.as_bytes()),
+        }
+    }
+
+    pub(crate) fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+// An empty NO_COLOR doesn't count, as in the no-color.org convention.
+fn use_color(requested: bool, no_color: Option<&OsStr>, terminal: bool) -> bool {
+    requested && no_color.is_none_or(|v| v.is_empty()) && terminal
+}
+
+fn color_code(level: LogLevel) -> Option<&'static str> {
+    match level {
+        LogLevel::Trace | LogLevel::Debug => Some("\x1b[90m"),
+        LogLevel::Info => None,
+        LogLevel::Warn => Some("\x1b[33m"),
+        LogLevel::Error => Some("\x1b[31m"),
+        LogLevel::Critical => Some("\x1b[1;31m"),
+        LogLevel::Off => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::InMemoryWriter;
+
+    fn write_all_levels(color: bool) -> String {
+        let writer = InMemoryWriter::new();
+        let mut console = Console::new(Box::new(writer.clone()), color);
+        for level in [
+            LogLevel::Debug,
+            LogLevel::Info,
+            LogLevel::Warn,
+            LogLevel::Error,
+            LogLevel::Critical,
+        ] {
+            console
+                .write_record(level, &format!("{}\n", level))
+                .unwrap();
+        }
+        writer.contents_string()
+    }
+
+    #[test]
+    fn levels_get_their_colors() {
+        assert_eq!(
+            write_all_levels(true),
+            "\x1b[90mDEBUG\x1b[0m\n\
+             INFO\n\
+             \x1b[33mWARN\x1b[0m\n\
+             \x1b[31mERROR\x1b[0m\n\
+             \x1b[1;31mCRITICAL\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn no_escape_codes_without_color() {
+        assert_eq!(
+            write_all_levels(false),
+            "DEBUG\nINFO\nWARN\nERROR\nCRITICAL\n"
+        );
+    }
+
+    #[test]
+    fn color_needs_a_terminal_and_no_no_color() {
+        assert!(use_color(true, None, true));
+        assert!(use_color(true, Some(OsStr::new("")), true));
+        assert!(!use_color(true, Some(OsStr::new("1")), true));
+        assert!(!use_color(true, None, false));
+        assert!(!use_color(false, None, true));
+    }
+}
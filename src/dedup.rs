@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    logger::LogLevel,
+    record::{LogValue, Record},
+};
+
+// The last record written in dedup mode, and how many identical ones have been
+// swallowed since.
+#[derive(Debug)]
+pub(crate) struct LastRecord {
+    pub(crate) level: LogLevel,
+    pub(crate) target: Option<String>,
+    pub(crate) message: String,
+    // Bound and call site fields together.
+    pub(crate) fields: Vec<(String, LogValue)>,
+    // Where the summary is reported from.
+    pub(crate) file: String,
+    pub(crate) line: u32,
+    pub(crate) repeated: u64,
+    // When the record was written or last reported, for the max interval.
+    pub(crate) since: DateTime<Utc>,
+}
+
+impl LastRecord {
+    pub(crate) fn new(record: &Record, now: DateTime<Utc>) -> Self {
+        Self {
+            level: record.level,
+            target: record.target.map(str::to_string),
+            message: record.message.to_string(),
+            fields: record
+                .fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+            file: record.file.to_string(),
+            line: record.line,
+            repeated: 0,
+            since: now,
+        }
+    }
+
+    // Records with the same level, target, message and fields are the same,
+    // wherever they were logged from.
+    pub(crate) fn matches(&self, record: &Record) -> bool {
+        self.level == record.level
+            && self.target.as_deref() == record.target
+            && self.message == record.message
+            && self.fields.len() == record.fields.len()
+            && self.fields.iter().zip(record.fields).all(
+                |((key, value), (other_key, other_value))| key == other_key && value == other_value,
+            )
+    }
+
+    pub(crate) fn summary(&self) -> String {
+        format!("last message repeated {} times", self.repeated)
+    }
+}
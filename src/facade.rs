@@ -0,0 +1,69 @@
+use log::{Level, LevelFilter, Metadata, Record};
+
+use crate::logger::{LogLevel, Logger};
+
+// Routes records from the `log` crate macros into a Logger.
+struct LogFacade(Logger);
+
+// `log` has no Critical level.
+fn to_log_level(level: Level) -> LogLevel {
+    match level {
+        Level::Trace => LogLevel::Trace,
+        Level::Debug => LogLevel::Debug,
+        Level::Info => LogLevel::Info,
+        Level::Warn => LogLevel::Warn,
+        Level::Error => LogLevel::Error,
+    }
+}
+
+fn to_level_filter(level: LogLevel) -> LevelFilter {
+    match level {
+        LogLevel::Trace => LevelFilter::Trace,
+        LogLevel::Debug => LevelFilter::Debug,
+        LogLevel::Info => LevelFilter::Info,
+        LogLevel::Warn => LevelFilter::Warn,
+        LogLevel::Error | LogLevel::Critical => LevelFilter::Error,
+        LogLevel::Off => LevelFilter::Off,
+    }
+}
+
+impl log::Log for LogFacade {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.0
+            .level_enabled_for(to_log_level(metadata.level()), metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        let metadata = record.metadata();
+        if !self
+            .0
+            .should_log(to_log_level(metadata.level()), metadata.target())
+        {
+            return;
+        }
+
+        self.0.write(
+            to_log_level(record.level()),
+            Some(metadata.target()),
+            &record.args().to_string(),
+            record.file().unwrap_or("<unknown>"),
+            record.line().unwrap_or(0),
+            &[],
+        );
+    }
+
+    fn flush(&self) {
+        self.0.flush();
+    }
+}
+
+pub(crate) fn install(logger: Logger) -> Result<(), log::SetLoggerError> {
+    let max_level = to_level_filter(logger.min_level());
+    log::set_boxed_logger(Box::new(LogFacade(logger)))?;
+    log::set_max_level(max_level);
+    Ok(())
+}
+
+pub(crate) fn update_max_level(logger: &Logger) {
+    log::set_max_level(to_level_filter(logger.min_level()));
+}
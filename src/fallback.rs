@@ -0,0 +1,38 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+const FIRST_RETRY: TimeDelta = TimeDelta::seconds(1);
+const MAX_RETRY: TimeDelta = TimeDelta::seconds(60);
+
+// A stretch of time where the output kept failing and records went to stderr
+// instead. The output is retried with exponential backoff.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Outage {
+    since: DateTime<Utc>,
+    backoff: TimeDelta,
+    pub(crate) retry_at: DateTime<Utc>,
+    pub(crate) records: u64,
+}
+
+impl Outage {
+    pub(crate) fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            since: now,
+            backoff: FIRST_RETRY,
+            retry_at: now + FIRST_RETRY,
+            records: 0,
+        }
+    }
+
+    pub(crate) fn retry_failed(&mut self, now: DateTime<Utc>) {
+        self.backoff = (self.backoff * 2).min(MAX_RETRY);
+        self.retry_at = now + self.backoff;
+    }
+
+    pub(crate) fn summary(&self, now: DateTime<Utc>) -> String {
+        format!(
+            "log output restored after {}s, {} records were written to stderr",
+            now.signed_duration_since(self.since).num_seconds(),
+            self.records
+        )
+    }
+}
@@ -0,0 +1,526 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+use crate::{
+    audit::Chain,
+    logger::{LogConfig, LoggerError, SyncMode, TimeAlignment, Timezone},
+};
+
+// The offset is the configured timezone's, e.g. `+0000` for UTC.
+const FILE_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H-%M-%S-%9f%z";
+// How files were named before timezones could be configured.
+const LEGACY_FILE_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H-%M-%S-%9fTUTC";
+
+#[derive(Debug)]
+pub(crate) struct LogFile {
+    pub(crate) file: BufWriter<File>,
+    pub(crate) path: PathBuf,
+    size: u64,
+    opened_at: DateTime<Utc>,
+    sync_mode: SyncMode,
+    // Only in audit mode.
+    chain: Option<Chain>,
+}
+
+impl LogFile {
+    pub(crate) fn create(config: &LogConfig, now: DateTime<Utc>) -> Result<Self, LoggerError> {
+        let mut log_file = Self::open_new(config, now)?;
+        log_file.start(config, None)?;
+        Ok(log_file)
+    }
+
+    fn open_new(config: &LogConfig, now: DateTime<Utc>) -> Result<Self, LoggerError> {
+        let dir = config.directory();
+        let file_prefix = config.file_prefix();
+        fs::create_dir_all(dir).map_err(|source| LoggerError::CreateDir {
+            path: dir.to_path_buf(),
+            source,
+        })?;
+
+        // Never truncate an existing file: if the name is taken (two rotations or
+        // two loggers within the clock's resolution), add the next free `.N`.
+        let rolling_config = config.rolling_config();
+        let local = config.timezone().to_local(now);
+        let timestamp = match rolling_config.time_alignment() {
+            TimeAlignment::Elapsed => local.format(FILE_TIMESTAMP_FORMAT),
+            TimeAlignment::Aligned => local.format(rolling_config.time_threshold().period_format()),
+        }
+        .to_string();
+        let mut sequence = 0;
+        let (file, path) = loop {
+            let name = match sequence {
+                0 => format!("{}{}.log", file_prefix, timestamp),
+                n => format!("{}{}.{}.log", file_prefix, timestamp, n),
+            };
+            let path = dir.join(name);
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => {
+                    lock(&file);
+                    break (file, path);
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => sequence += 1,
+                Err(source) => return Err(LoggerError::CreateFile { path, source }),
+            }
+        };
+        Ok(Self {
+            file: BufWriter::new(file),
+            path,
+            size: 0,
+            opened_at: now,
+            sync_mode: config.sync_mode(),
+            chain: None,
+        })
+    }
+
+    // Writes what goes before the first record: the genesis line in audit mode,
+    // continuing from `previous` after a rotation, and the CSV header. Neither
+    // counts towards the size threshold, so an oversized first record doesn't
+    // roll straight away.
+    fn start(&mut self, config: &LogConfig, previous: Option<[u8; 32]>) -> Result<(), LoggerError> {
+        let mut preamble = String::new();
+        if config.audit() {
+            let (chain, genesis) = Chain::start(&self.path, previous);
+            preamble.push_str(&genesis);
+            self.chain = Some(chain);
+        }
+        if let Some(header) = config.csv_header() {
+            match &mut self.chain {
+                Some(chain) => preamble.push_str(&chain.seal(&header)),
+                None => preamble.push_str(&header),
+            }
+        }
+        self.file
+            .write_all(preamble.as_bytes())
+            .map_err(|source| LoggerError::Write {
+                path: self.path.clone(),
+                source,
+            })
+    }
+
+    // Reopens the newest `<prefix><timestamp>[.N].log` in the directory for
+    // appending, as long as it is still within both rolling thresholds. Returns
+    // None when there is nothing suitable so the caller can create a new file.
+    // Audit mode always starts a new file, since appending would mean trusting
+    // the old file's last hash.
+    pub(crate) fn reuse_latest(config: &LogConfig, now: DateTime<Utc>) -> Option<Self> {
+        if config.audit() {
+            return None;
+        }
+        let ((opened_at, _), path) = list_log_files(config).ok()?.into_iter().max()?;
+        let size = fs::metadata(&path).ok()?.len();
+        if size >= config.rolling_config().size_threshold() || time_to_roll(config, opened_at, now)
+        {
+            return None;
+        }
+
+        let file = OpenOptions::new().append(true).open(&path).ok()?;
+        // Another logger is still writing to it.
+        if !lock(&file) {
+            return None;
+        }
+        Some(Self {
+            file: BufWriter::new(file),
+            path,
+            size,
+            opened_at,
+            sync_mode: config.sync_mode(),
+            chain: None,
+        })
+    }
+
+    // Callers roll first with check_and_update_rolling, whose errors don't stop
+    // the record from going into the current file.
+    pub(crate) fn write_record(&mut self, record: &str) -> Result<(), LoggerError> {
+        let sealed;
+        let record = match &mut self.chain {
+            Some(chain) => {
+                sealed = chain.seal(record);
+                &sealed
+            }
+            None => record,
+        };
+        self.file
+            .write_all(record.as_bytes())
+            .map_err(|source| LoggerError::Write {
+                path: self.path.clone(),
+                source,
+            })?;
+        self.size += record.len() as u64;
+        if self.sync_mode == SyncMode::EveryRecord {
+            self.flush_and_sync()?;
+        }
+        Ok(())
+    }
+
+    // Rolls over before a record that would push the file past the size threshold,
+    // so records are never split. A record larger than the threshold still goes
+    // into a fresh file on its own.
+    //
+    // The time threshold is measured from when the current file was opened (or
+    // its period started, when aligned), so a long idle gap results in a single
+    // new file on the next record.
+    pub(crate) fn check_and_update_rolling(
+        &mut self,
+        record_len: u64,
+        now: DateTime<Utc>,
+        config: &LogConfig,
+    ) -> Result<(), LoggerError> {
+        let rolling_config = config.rolling_config();
+        let size_threshold = rolling_config.size_threshold();
+        if (self.size > 0 && self.size + record_len > size_threshold)
+            || time_to_roll(config, self.opened_at, now)
+        {
+            self.roll(config, now)?;
+        }
+        Ok(())
+    }
+
+    // Moves on to a new timestamped file.
+    pub(crate) fn roll(
+        &mut self,
+        config: &LogConfig,
+        now: DateTime<Utc>,
+    ) -> Result<(), LoggerError> {
+        // The old file is made durable before the next one exists, so a crash
+        // mid-rotation never leaves the newer file ahead of an unsynced one.
+        if self.sync_mode != SyncMode::None {
+            self.flush_and_sync()?;
+        }
+        // Loggers sharing the directory take turns rotating and cleaning up.
+        let _dir_lock = lock_dir(config);
+        let mut next = Self::open_new(config, now)?;
+        let mut head = None;
+        if let Some(chain) = &mut self.chain {
+            let (trailer, next_genesis) = chain.close(&next.path);
+            self.file
+                .write_all(trailer.as_bytes())
+                .map_err(|source| LoggerError::Write {
+                    path: self.path.clone(),
+                    source,
+                })?;
+            head = Some(next_genesis);
+        }
+        next.start(config, head)?;
+        let previous = std::mem::replace(self, next);
+        previous.close()?;
+        if let Some(max_files) = config.rolling_config().max_files() {
+            self.remove_old_files(config, max_files);
+        }
+        if config.latest_symlink() {
+            self.update_latest_link(config.file_prefix());
+        }
+        Ok(())
+    }
+
+    pub(crate) fn flush(&mut self) -> Result<(), LoggerError> {
+        // EveryRecord already synced in write_record, so it isn't synced twice.
+        match self.sync_mode {
+            SyncMode::OnFlush => self.flush_and_sync(),
+            SyncMode::None | SyncMode::EveryRecord => {
+                self.file.flush().map_err(|source| LoggerError::Write {
+                    path: self.path.clone(),
+                    source,
+                })
+            }
+        }
+    }
+
+    fn flush_and_sync(&mut self) -> Result<(), LoggerError> {
+        self.file
+            .flush()
+            .and_then(|()| self.file.get_ref().sync_data())
+            .map_err(|source| LoggerError::Write {
+                path: self.path.clone(),
+                source,
+            })
+    }
+
+    // Opens the file again by path, creating it if it is gone, for when the
+    // current handle keeps failing. Returns whatever the old handle still had
+    // buffered. A new or empty file gets the same preamble as a created one,
+    // with an audit chain carrying on from the last record. If another logger
+    // has locked the file in the meantime, it is left to them and a new file
+    // starts instead.
+    pub(crate) fn reopen(
+        &mut self,
+        config: &LogConfig,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<u8>, LoggerError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|source| LoggerError::CreateFile {
+                path: self.path.clone(),
+                source,
+            })?;
+        // The old handle is closed first, since its own lock would keep the new
+        // one from locking the same file.
+        let previous = std::mem::replace(&mut self.file, BufWriter::new(file));
+        let (_, pending) = previous.into_parts();
+        let pending = pending.unwrap_or_default();
+        let head = self.chain.as_ref().map(Chain::head);
+        if !lock(self.file.get_ref()) {
+            let _dir_lock = lock_dir(config);
+            let mut next = Self::open_new(config, now)?;
+            next.start(config, head)?;
+            *self = next;
+            if config.latest_symlink() {
+                self.update_latest_link(config.file_prefix());
+            }
+            return Ok(pending);
+        }
+        self.size = self.file.get_ref().metadata().map(|m| m.len()).unwrap_or(0);
+        if self.size == 0 {
+            self.start(config, head)?;
+        }
+        Ok(pending)
+    }
+
+    // Writes to a file that was deleted from under us still succeed, into an
+    // inode nothing can read any more, so only its link count gives it away.
+    #[cfg(unix)]
+    pub(crate) fn is_deleted(&self) -> bool {
+        use std::os::unix::fs::MetadataExt;
+
+        self.file
+            .get_ref()
+            .metadata()
+            .is_ok_and(|metadata| metadata.nlink() == 0)
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn is_deleted(&self) -> bool {
+        false
+    }
+
+    fn close(mut self) -> Result<(), LoggerError> {
+        self.flush()
+    }
+
+    // Points `<prefix>latest.log` at this file. The link is built under a temporary
+    // name and renamed over the old one, so readers never see it missing. Windows
+    // can't create symlinks without extra privileges, so there the path is written
+    // to `<prefix>latest.path` instead.
+    pub(crate) fn update_latest_link(&self, file_prefix: &str) {
+        let (Some(dir), Some(file_name)) = (self.path.parent(), self.path.file_name()) else {
+            return;
+        };
+        if let Err(e) = write_latest_link(dir, file_prefix, Path::new(file_name)) {
+            eprintln!(
+                "Could not point the latest log link at {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+
+    // Only files named `<prefix><timestamp>[.N].log` are considered, so anything else
+    // in the directory is left alone. The active file is always the newest one.
+    fn remove_old_files(&self, config: &LogConfig, max_files: usize) {
+        let dir = config.directory();
+        let mut files = match list_log_files(config) {
+            Ok(files) => files,
+            Err(e) => {
+                eprintln!("Could not list log directory {}: {}", dir.display(), e);
+                return;
+            }
+        };
+        let keep = max_files.max(1);
+        if files.len() <= keep {
+            return;
+        }
+
+        files.sort();
+        for (_, path) in &files[..files.len() - keep] {
+            // Still another logger's active file.
+            if is_locked(path) {
+                continue;
+            }
+            if let Err(e) = fs::remove_file(path) {
+                eprintln!("Could not remove old log file {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+// Advisory locks keep loggers sharing a directory out of each other's files.
+// Returns false only when another handle holds the lock; filesystems without
+// lock support just go unlocked. Locks go away when the file is closed, so
+// dropping or unwinding past a LogFile releases them. Windows locks aren't
+// advisory and would lock out log viewers, so nothing is locked there.
+#[cfg(unix)]
+fn lock(file: &File) -> bool {
+    !matches!(file.try_lock(), Err(fs::TryLockError::WouldBlock))
+}
+
+#[cfg(not(unix))]
+fn lock(_file: &File) -> bool {
+    true
+}
+
+fn is_locked(path: &Path) -> bool {
+    File::open(path).is_ok_and(|file| !lock(&file))
+}
+
+// Held for the duration of a rotation. Rotation goes ahead unlocked if the lock
+// file can't be created.
+fn lock_dir(config: &LogConfig) -> Option<File> {
+    let path = config
+        .directory()
+        .join(format!(".{}lock", config.file_prefix()));
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .ok()?;
+    #[cfg(unix)]
+    file.lock().ok()?;
+    Some(file)
+}
+
+// The newest file is the one a running logger with the same prefix would have
+// open.
+pub(crate) fn check_unlocked(config: &LogConfig) -> Result<(), LoggerError> {
+    let newest = list_log_files(config)
+        .ok()
+        .and_then(|files| files.into_iter().max());
+    match newest {
+        Some((_, path)) if is_locked(&path) => Err(LoggerError::Locked { path }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(unix)]
+fn write_latest_link(dir: &Path, file_prefix: &str, target: &Path) -> io::Result<()> {
+    let link = dir.join(format!("{}latest.log", file_prefix));
+    let temp = dir.join(format!(".{}latest.log.tmp", file_prefix));
+    let _ = fs::remove_file(&temp);
+    std::os::unix::fs::symlink(target, &temp)?;
+    fs::rename(&temp, link)
+}
+
+#[cfg(not(unix))]
+fn write_latest_link(dir: &Path, file_prefix: &str, target: &Path) -> io::Result<()> {
+    let link = dir.join(format!("{}latest.path", file_prefix));
+    fs::write(link, dir.join(target).to_string_lossy().as_bytes())
+}
+
+fn time_to_roll(config: &LogConfig, opened_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    let time_threshold = config.rolling_config().time_threshold();
+    match config.rolling_config().time_alignment() {
+        TimeAlignment::Elapsed => {
+            now.signed_duration_since(opened_at).num_seconds() >= time_threshold as i64
+        }
+        TimeAlignment::Aligned => {
+            let timezone = config.timezone();
+            time_threshold.period_start(now, timezone)
+                > time_threshold.period_start(opened_at, timezone)
+        }
+    }
+}
+
+// A file's timestamp and sequence number, which together order the files.
+type FileOrder = (DateTime<Utc>, u32);
+
+fn list_log_files(config: &LogConfig) -> io::Result<Vec<(FileOrder, PathBuf)>> {
+    Ok(fs::read_dir(config.directory())?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let file_name = path.file_name()?.to_str()?;
+            let timestamp =
+                parse_file_timestamp(file_name, config.file_prefix(), config.timezone())?;
+            Some((timestamp, path))
+        })
+        .collect())
+}
+
+fn parse_file_timestamp(
+    file_name: &str,
+    file_prefix: &str,
+    timezone: Timezone,
+) -> Option<FileOrder> {
+    let stem = file_name.strip_prefix(file_prefix)?.strip_suffix(".log")?;
+    let (timestamp, sequence) = match stem.rsplit_once('.') {
+        Some((timestamp, sequence)) => (timestamp, sequence.parse().ok()?),
+        None => (stem, 0),
+    };
+    Some((parse_timestamp(timestamp, timezone)?, sequence))
+}
+
+// Full timestamps carry their offset, so files keep their order when the
+// timezone changes. Aligned files carry only as much of the timestamp as their
+// period needs, in the configured timezone, so the missing parts are filled in
+// with the start of the period.
+fn parse_timestamp(timestamp: &str, timezone: Timezone) -> Option<DateTime<Utc>> {
+    if let Ok(full) = DateTime::parse_from_str(timestamp, FILE_TIMESTAMP_FORMAT) {
+        return Some(full.to_utc());
+    }
+    if let Ok(full) = NaiveDateTime::parse_from_str(timestamp, LEGACY_FILE_TIMESTAMP_FORMAT) {
+        return Some(full.and_utc());
+    }
+    if let Ok(week) = NaiveDate::parse_from_str(&format!("{}-1", timestamp), "%G-W%V-%u") {
+        return Some(timezone.to_utc(week.and_time(NaiveTime::MIN)));
+    }
+    let padding = match timestamp.len() {
+        4 => "-01-01T00-00-00",
+        7 => "-01T00-00-00",
+        10 => "T00-00-00",
+        13 => "-00-00",
+        16 => "-00",
+        19 => "",
+        _ => return None,
+    };
+    let start =
+        NaiveDateTime::parse_from_str(&format!("{}{}", timestamp, padding), "%Y-%m-%dT%H-%M-%S")
+            .ok()?;
+    Some(timezone.to_utc(start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EAST: Timezone = Timezone::FixedOffset(2 * 3600);
+
+    fn parse(file_name: &str, timezone: Timezone) -> FileOrder {
+        parse_file_timestamp(file_name, "App_", timezone).unwrap()
+    }
+
+    #[test]
+    fn file_timestamps_are_ordered_in_utc() {
+        let (east, _) = parse("App_2024-01-01T12-30-00-000000000+0200.log", Timezone::Utc);
+        let (utc, _) = parse("App_2024-01-01T11-00-00-000000000+0000.log", Timezone::Utc);
+        assert!(east < utc);
+        assert_eq!(east.to_rfc3339(), "2024-01-01T10:30:00+00:00");
+    }
+
+    #[test]
+    fn full_names_ignore_the_configured_timezone() {
+        let name = "App_2024-01-01T12-30-00-000000000+0200.log";
+        assert_eq!(parse(name, EAST), parse(name, Timezone::Utc));
+    }
+
+    #[test]
+    fn aligned_names_are_in_the_configured_timezone() {
+        let (start, _) = parse("App_2024-01-02.log", EAST);
+        assert_eq!(start.to_rfc3339(), "2024-01-01T22:00:00+00:00");
+        let (week, _) = parse("App_2024-W02.log", Timezone::Utc);
+        assert_eq!(week.to_rfc3339(), "2024-01-08T00:00:00+00:00");
+    }
+
+    #[test]
+    fn legacy_and_sequenced_names_still_parse() {
+        let legacy = parse("App_2024-01-01T10-00-00-000000000TUTC.log", EAST);
+        let sequenced = parse("App_2024-01-01T10-00-00-000000000+0000.2.log", EAST);
+        assert_eq!(legacy.1, 0);
+        assert_eq!(sequenced.1, 2);
+        assert_eq!(legacy.0, sequenced.0);
+    }
+}
@@ -0,0 +1,57 @@
+use std::{env, fmt, process};
+
+// Checked before asking the OS, e.g. for containers whose hostname is a random id.
+const HOSTNAME_VAR: &str = "LOGTAR_HOSTNAME";
+
+// Where records come from, captured once when the logger is built.
+#[derive(Debug, Clone)]
+pub(crate) struct HostInfo {
+    pub(crate) name: String,
+    pub(crate) pid: u32,
+}
+
+impl HostInfo {
+    // An instance name wins over the environment, which wins over the OS.
+    pub(crate) fn detect(instance_name: Option<&str>) -> Self {
+        let name = match instance_name {
+            Some(name) => name.to_string(),
+            None => env::var(HOSTNAME_VAR)
+                .ok()
+                .filter(|name| !name.is_empty())
+                .or_else(hostname)
+                .unwrap_or_else(|| "unknown".to_string()),
+        };
+        Self {
+            name,
+            pid: process::id(),
+        }
+    }
+}
+
+impl fmt::Display for HostInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.name, self.pid)
+    }
+}
+
+#[cfg(unix)]
+fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: the buffer is valid for its whole length, and gethostname writes
+    // at most that many bytes.
+    let result = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) };
+    if result != 0 {
+        return None;
+    }
+    // The name may not be NUL-terminated if it was truncated.
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    let name = String::from_utf8_lossy(&buf[..len]).into_owned();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(not(unix))]
+fn hostname() -> Option<String> {
+    env::var("COMPUTERNAME")
+        .ok()
+        .filter(|name| !name.is_empty())
+}
@@ -0,0 +1,276 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, RecvTimeoutError, SyncSender, TrySendError},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use http_body_util::Full;
+use hyper::{body::Bytes, header, Method, Request, Uri};
+use hyper_util::{
+    client::legacy::{connect::HttpConnector, Client},
+    rt::TokioExecutor,
+};
+use tokio::sync::watch;
+
+use crate::logger::ConfigError;
+
+#[cfg(feature = "https")]
+type Connector = hyper_rustls::HttpsConnector<HttpConnector>;
+#[cfg(not(feature = "https"))]
+type Connector = HttpConnector;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// How long sending what is left may take once the sink is dropped, including a
+// batch that was already on its way.
+const FINAL_TIMEOUT: Duration = Duration::from_secs(5);
+const ATTEMPTS: u32 = 4;
+const FIRST_RETRY: Duration = Duration::from_millis(500);
+
+/// Where and how often an HTTP sink posts its batches. https:// URLs need the
+/// `https` feature, which verifies servers against the bundled webpki roots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpSinkConfig {
+    url: Uri,
+    auth_header: Option<String>,
+    batch_size: usize,
+    flush_interval: Duration,
+    buffer_capacity: usize,
+}
+
+impl HttpSinkConfig {
+    /// Fails with ConfigError::InvalidUrl for malformed or unsupported URLs.
+    pub fn new(url: &str) -> Result<Self, ConfigError> {
+        let invalid = |reason: &str| ConfigError::InvalidUrl {
+            url: url.to_string(),
+            reason: reason.to_string(),
+        };
+        let parsed: Uri = url.parse().map_err(|_| invalid("not a valid URL"))?;
+        match parsed.scheme_str() {
+            Some("http") => {}
+            #[cfg(feature = "https")]
+            Some("https") => {}
+            #[cfg(not(feature = "https"))]
+            Some("https") => return Err(invalid("https:// URLs need the https feature")),
+            _ => return Err(invalid("expected an http:// or https:// URL")),
+        }
+        if parsed.host().is_none() {
+            return Err(invalid("the URL has no host"));
+        }
+        Ok(Self {
+            url: parsed,
+            auth_header: None,
+            batch_size: 100,
+            flush_interval: Duration::from_secs(1),
+            buffer_capacity: 10_000,
+        })
+    }
+
+    /// Sent as the Authorization header, e.g. `Bearer <token>`.
+    pub fn with_auth_header(mut self, value: String) -> Self {
+        self.auth_header = Some(value);
+        self
+    }
+
+    /// Records per POST; defaults to 100.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// A partial batch is sent once it is this old.
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// Records waiting to be batched; past this they are dropped and counted.
+    pub fn with_buffer_capacity(mut self, capacity: usize) -> Self {
+        self.buffer_capacity = capacity.max(1);
+        self
+    }
+}
+
+// Posts records as JSON arrays from a thread of its own. Server errors and
+// failed connections are retried with backoff; a 4xx response drops the batch.
+pub(crate) struct HttpWriter {
+    sender: Option<SyncSender<String>>,
+    // The shutdown deadline, set on drop.
+    shutdown: watch::Sender<Option<Instant>>,
+    thread: Option<JoinHandle<()>>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl HttpWriter {
+    pub(crate) fn spawn(config: HttpSinkConfig) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(config.buffer_capacity);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let thread_dropped = Arc::clone(&dropped);
+        let (shutdown, thread_shutdown) = watch::channel(None);
+        let thread = thread::Builder::new()
+            .name("logtar-http".to_string())
+            .spawn(move || run(&config, &receiver, thread_shutdown, &thread_dropped))
+            .ok();
+        Self {
+            sender: Some(sender),
+            shutdown,
+            thread,
+            dropped,
+        }
+    }
+
+    pub(crate) fn push(&self, line: &str) {
+        let line = line.strip_suffix('\n').unwrap_or(line).to_string();
+        let sent = match &self.sender {
+            Some(sender) => sender.try_send(line),
+            None => Err(TrySendError::Disconnected(line)),
+        };
+        if sent.is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+// Closing the channel makes the thread send what it has left before it exits.
+// Everything, a request already in flight included, has to finish within
+// FINAL_TIMEOUT; what doesn't is dropped.
+impl Drop for HttpWriter {
+    fn drop(&mut self) {
+        self.shutdown
+            .send_replace(Some(Instant::now() + FINAL_TIMEOUT));
+        self.sender = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run(
+    config: &HttpSinkConfig,
+    receiver: &mpsc::Receiver<String>,
+    shutdown: watch::Receiver<Option<Instant>>,
+    dropped: &AtomicU64,
+) {
+    let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    else {
+        return;
+    };
+    let Some(connector) = connector() else {
+        return;
+    };
+    let client = Client::builder(TokioExecutor::new()).build(connector);
+    let mut batch = Vec::new();
+    let mut deadline = Instant::now() + config.flush_interval;
+    loop {
+        let wait = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(wait) {
+            Ok(line) => {
+                batch.push(line);
+                if batch.len() < config.batch_size {
+                    continue;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                let sent = batch.is_empty()
+                    || runtime.block_on(send(&client, config, &batch, shutdown.clone()));
+                if !sent {
+                    dropped.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                }
+                return;
+            }
+        }
+        if !batch.is_empty() && !runtime.block_on(send(&client, config, &batch, shutdown.clone())) {
+            dropped.fetch_add(batch.len() as u64, Ordering::Relaxed);
+        }
+        batch.clear();
+        deadline = Instant::now() + config.flush_interval;
+    }
+}
+
+#[cfg(feature = "https")]
+fn connector() -> Option<Connector> {
+    let builder = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_provider_and_webpki_roots(rustls::crypto::ring::default_provider());
+    match builder {
+        Ok(builder) => Some(builder.https_or_http().enable_http1().build()),
+        Err(e) => {
+            eprintln!("HTTP sink disabled, could not set up TLS: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "https"))]
+fn connector() -> Option<Connector> {
+    Some(HttpConnector::new())
+}
+
+// Posts the batch, giving up at the shutdown deadline once there is one.
+async fn send(
+    client: &Client<Connector, Full<Bytes>>,
+    config: &HttpSinkConfig,
+    batch: &[String],
+    mut shutdown: watch::Receiver<Option<Instant>>,
+) -> bool {
+    let retries = shutdown.clone();
+    let deadline = async move {
+        // Without a deadline the writer is gone, so the default one applies.
+        let deadline = match shutdown.wait_for(Option::is_some).await {
+            Ok(deadline) => deadline.unwrap_or_else(Instant::now),
+            Err(_) => Instant::now() + FINAL_TIMEOUT,
+        };
+        tokio::time::sleep_until(deadline.into()).await;
+    };
+    tokio::select! {
+        accepted = post(client, config, batch, &retries) => accepted,
+        () = deadline => false,
+    }
+}
+
+// Returns whether the batch was accepted. Failures aren't retried once the sink
+// is shutting down.
+async fn post(
+    client: &Client<Connector, Full<Bytes>>,
+    config: &HttpSinkConfig,
+    batch: &[String],
+    shutdown: &watch::Receiver<Option<Instant>>,
+) -> bool {
+    let body = Bytes::from(format!("[{}]", batch.join(",")));
+    let mut backoff = FIRST_RETRY;
+    for attempt in 1..=ATTEMPTS {
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(config.url.clone())
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(auth) = &config.auth_header {
+            request = request.header(header::AUTHORIZATION, auth);
+        }
+        let Ok(request) = request.body(Full::new(body.clone())) else {
+            return false;
+        };
+        match tokio::time::timeout(REQUEST_TIMEOUT, client.request(request)).await {
+            Ok(Ok(response)) if response.status().is_client_error() => return false,
+            Ok(Ok(response)) if !response.status().is_server_error() => return true,
+            // Server errors, connection failures and timeouts are worth a retry.
+            _ => {}
+        }
+        if shutdown.borrow().is_some() {
+            return false;
+        }
+        if attempt < ATTEMPTS {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+    false
+}
@@ -0,0 +1,221 @@
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{self, Write},
+    mem,
+    os::{
+        fd::{AsRawFd, FromRawFd},
+        unix::{ffi::OsStrExt, net::UnixDatagram},
+    },
+    path::{Path, PathBuf},
+    ptr,
+};
+
+use crate::{
+    logger::LogLevel,
+    record::{self, Record},
+    syslog,
+};
+
+pub(crate) const SOCKET_PATH: &str = "/run/systemd/journal/socket";
+
+// journald refuses single fields larger than this (DATA_SIZE_MAX).
+const MAX_FIELD_LEN: usize = 64 * 1024 * 1024;
+
+const RESERVED_NAMES: [&str; 4] = ["MESSAGE", "PRIORITY", "CODE_FILE", "CODE_LINE"];
+
+// The record as `NAME=value` entries separated by NULs, which the writer turns
+// into the native protocol. Values from C callers can't contain NULs either, so
+// any in ours are dropped. Field names are uppercased and cleaned up to what
+// journald accepts; ones clashing with the built-in names get a "FIELD_" prefix.
+pub(crate) fn format(record: &Record) -> String {
+    let mut entry = String::new();
+    push_field(&mut entry, "MESSAGE", record.message);
+    let priority = syslog::severity(record.level).to_string();
+    push_field(&mut entry, "PRIORITY", &priority);
+    push_field(&mut entry, "CODE_FILE", record.file);
+    push_field(&mut entry, "CODE_LINE", &record.line.to_string());
+    for (key, value) in record.fields {
+        let name = field_name(key);
+        let name = if RESERVED_NAMES.contains(&name.as_str()) {
+            format!("FIELD_{}", name)
+        } else {
+            name
+        };
+        push_field(&mut entry, &name, &value.to_string());
+    }
+    entry
+}
+
+// A record laid out in some other format still needs a priority.
+pub(crate) fn message_entry(level: LogLevel, line: &str) -> String {
+    let mut entry = String::new();
+    push_field(
+        &mut entry,
+        "MESSAGE",
+        line.strip_suffix('\n').unwrap_or(line),
+    );
+    push_field(&mut entry, "PRIORITY", &syslog::severity(level).to_string());
+    entry
+}
+
+fn push_field(entry: &mut String, name: &str, value: &str) {
+    let mut value = Cow::Borrowed(value);
+    if value.contains('\0') {
+        value = Cow::Owned(value.replace('\0', ""));
+    }
+    record::truncate(&mut value, MAX_FIELD_LEN);
+    entry.push_str(name);
+    entry.push('=');
+    entry.push_str(&value);
+    entry.push('\0');
+}
+
+// Names are at most 64 chars of A-Z, 0-9 and '_', and can't start with a digit
+// or with '_', which marks fields only journald itself may set.
+fn field_name(key: &str) -> String {
+    let name: String = key
+        .chars()
+        .map(|c| match c {
+            c if c.is_ascii_alphanumeric() => c.to_ascii_uppercase(),
+            _ => '_',
+        })
+        .collect();
+    let name = name.trim_start_matches('_');
+    let name = match name.chars().next() {
+        None => "FIELD".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("FIELD_{}", name),
+        Some(_) => name.to_string(),
+    };
+    name.chars().take(64).collect()
+}
+
+// Values without line breaks use the `NAME=value\n` form; the others need the
+// binary one, `NAME\n` followed by the length as a little-endian u64.
+fn encode(entry: &str) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(entry.len() + 16);
+    for field in entry.split_terminator('\0') {
+        let (name, value) = field.split_once('=').unwrap_or((field, ""));
+        if value.contains('\n') {
+            payload.extend_from_slice(name.as_bytes());
+            payload.push(b'\n');
+            payload.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            payload.extend_from_slice(name.as_bytes());
+            payload.push(b'=');
+        }
+        payload.extend_from_slice(value.as_bytes());
+        payload.push(b'\n');
+    }
+    payload
+}
+
+// Without a journal socket, e.g. outside systemd, the writer warns once and
+// then drops everything.
+pub(crate) struct JournaldWriter {
+    path: PathBuf,
+    socket: Option<UnixDatagram>,
+}
+
+impl JournaldWriter {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        let socket = if path.exists() {
+            UnixDatagram::unbound()
+        } else {
+            Err(io::Error::new(io::ErrorKind::NotFound, "no journal socket"))
+        };
+        let socket = match socket {
+            Ok(socket) => Some(socket),
+            Err(e) => {
+                eprintln!(
+                    "journald sink disabled, can't use {}: {}",
+                    path.display(),
+                    e
+                );
+                None
+            }
+        };
+        Self { path, socket }
+    }
+
+    pub(crate) fn send(&mut self, entry: &str) -> io::Result<()> {
+        let Some(socket) = &self.socket else {
+            return Ok(());
+        };
+        let payload = encode(entry);
+        match socket.send_to(&payload, &self.path) {
+            Ok(_) => Ok(()),
+            // Too big for a datagram: journald also takes the entry as a sealed
+            // memfd passed over the socket.
+            Err(e) if matches!(e.raw_os_error(), Some(libc::EMSGSIZE | libc::ENOBUFS)) => {
+                send_memfd(socket, &self.path, &payload)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn send_memfd(socket: &UnixDatagram, path: &Path, payload: &[u8]) -> io::Result<()> {
+    // SAFETY: the name is a valid C string and the flags are valid for memfd_create.
+    let fd = unsafe {
+        libc::memfd_create(
+            c"logtar-journal".as_ptr(),
+            libc::MFD_ALLOW_SEALING | libc::MFD_CLOEXEC,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: fd was just created and nothing else owns it.
+    let mut memfd = unsafe { File::from_raw_fd(fd) };
+    memfd.write_all(payload)?;
+    let seals = libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE | libc::F_SEAL_SEAL;
+    // SAFETY: fd is a valid memfd created with MFD_ALLOW_SEALING.
+    if unsafe { libc::fcntl(fd, libc::F_ADD_SEALS, seals) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    send_fd(socket, path, fd)
+}
+
+// std has no stable way to pass descriptors, so this is sendmsg with a single
+// SCM_RIGHTS message and no data.
+fn send_fd(socket: &UnixDatagram, path: &Path, fd: libc::c_int) -> io::Result<()> {
+    // SAFETY: sockaddr_un is plain data, all zeroes is a valid value.
+    let mut addr: libc::sockaddr_un = unsafe { mem::zeroed() };
+    addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+    let path = path.as_os_str().as_bytes();
+    if path.len() >= addr.sun_path.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "journal socket path is too long",
+        ));
+    }
+    for (dst, src) in addr.sun_path.iter_mut().zip(path) {
+        *dst = *src as libc::c_char;
+    }
+
+    // SAFETY: CMSG_SPACE only computes a size.
+    let space = unsafe { libc::CMSG_SPACE(mem::size_of::<libc::c_int>() as u32) } as usize;
+    // u64s keep the buffer aligned for cmsghdr.
+    let mut control = vec![0u64; space.div_ceil(mem::size_of::<u64>())];
+    // SAFETY: msghdr is plain data, all zeroes is a valid value.
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_name = ptr::addr_of_mut!(addr).cast();
+    msg.msg_namelen = mem::size_of::<libc::sockaddr_un>() as libc::socklen_t;
+    msg.msg_control = control.as_mut_ptr().cast();
+    msg.msg_controllen = space as _;
+    // SAFETY: msg_control points at a buffer of CMSG_SPACE bytes for one c_int,
+    // so the first header and its data fit.
+    unsafe {
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of::<libc::c_int>() as u32) as _;
+        ptr::write_unaligned(libc::CMSG_DATA(cmsg).cast::<libc::c_int>(), fd);
+    }
+    // SAFETY: every pointer in msg stays valid for the call.
+    if unsafe { libc::sendmsg(socket.as_raw_fd(), &msg, libc::MSG_NOSIGNAL) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! A flexible logging library with rolling log files.
+//!
+//! ```no_run
+//! use logger::Logger;
+//!
+//! let logger = Logger::new(None);
+//! logger.info("Hello from Logtar!");
+//! ```
+
+mod audit;
+mod clock;
+mod console;
+mod dedup;
+mod facade;
+mod fallback;
+mod file;
+mod host;
+mod http;
+#[cfg(all(target_os = "linux", feature = "journald"))]
+mod journald;
+mod limiter;
+mod logger;
+mod macros;
+mod pattern;
+mod record;
+mod redact;
+#[cfg(all(unix, feature = "sighup"))]
+mod signal;
+mod sink;
+mod size;
+mod stats;
+mod syslog;
+mod tcp;
+mod worker;
+mod writer;
+
+pub use audit::{ChainError, ChainReport};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use http::HttpSinkConfig;
+pub use logger::{
+    ConfigError, FlushPolicy, LockConflict, LogConfig, LogFormat, LogLevel, Logger, LoggerError,
+    Multiline, OverflowPolicy, ParseLevelError, ReopenMode, RollingConfig, RollingSizeOptions,
+    RollingTimeOptions, SyncMode, TimeAlignment, TimestampPrecision, Timezone,
+};
+pub use record::LogValue;
+pub use sink::Sink;
+pub use stats::LoggerStats;
+pub use syslog::{Facility, SyslogConfig, SyslogTransport};
+pub use writer::InMemoryWriter;
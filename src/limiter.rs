@@ -0,0 +1,155 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::logger::LogLevel;
+
+#[derive(Debug)]
+struct Window {
+    started: DateTime<Utc>,
+    count: u32,
+    suppressed: u64,
+    level: LogLevel,
+}
+
+pub(crate) enum Decision {
+    Allow,
+    Suppress,
+}
+
+// The records a call site dropped in a window, to be reported as one summary.
+#[derive(Debug)]
+pub(crate) struct Suppressed {
+    pub(crate) file: String,
+    pub(crate) line: u32,
+    pub(crate) level: LogLevel,
+    pub(crate) count: u64,
+}
+
+impl Suppressed {
+    pub(crate) fn summary(&self) -> String {
+        format!(
+            "suppressed {} similar messages in the last second",
+            self.count
+        )
+    }
+}
+
+// Allows up to N records per call site in each one-second window. There's no
+// timer: closed windows are noticed on the next limited record from any site,
+// and whatever is still pending is reported on flush and drop.
+#[derive(Debug, Default)]
+pub(crate) struct RateLimiter {
+    windows: Mutex<HashMap<(String, u32), Window>>,
+    // Records suppressed and not yet reported, so sites that aren't dropping
+    // anything don't need the lock to look for closed windows.
+    pending: AtomicU64,
+}
+
+impl RateLimiter {
+    pub(crate) fn check(
+        &self,
+        file: &str,
+        line: u32,
+        level: LogLevel,
+        per_second: u32,
+        now: DateTime<Utc>,
+    ) -> (Decision, Vec<Suppressed>) {
+        let mut windows = self.lock();
+        // Closed windows that suppressed anything are reported first.
+        let closed = self.take(&mut windows, |window| is_closed(window, now));
+        let window = windows.entry((file.to_string(), line)).or_insert(Window {
+            started: now,
+            count: 0,
+            suppressed: 0,
+            level,
+        });
+        if is_closed(window, now) {
+            *window = Window {
+                started: now,
+                count: 0,
+                suppressed: 0,
+                level,
+            };
+        }
+        let decision = if window.count < per_second {
+            window.count += 1;
+            Decision::Allow
+        } else {
+            window.suppressed += 1;
+            window.level = level;
+            self.pending.fetch_add(1, Ordering::Relaxed);
+            Decision::Suppress
+        };
+        (decision, closed)
+    }
+
+    // Whether check would allow a record from the site at `now`, without
+    // counting one.
+    pub(crate) fn has_room(
+        &self,
+        file: &str,
+        line: u32,
+        per_second: u32,
+        now: DateTime<Utc>,
+    ) -> bool {
+        self.lock()
+            .get(&(file.to_string(), line))
+            .is_none_or(|window| is_closed(window, now) || window.count < per_second)
+    }
+
+    // The counts of windows that closed by `now`.
+    pub(crate) fn closed(&self, now: DateTime<Utc>) -> Vec<Suppressed> {
+        if self.pending.load(Ordering::Relaxed) == 0 {
+            return Vec::new();
+        }
+        self.take(&mut self.lock(), |window| is_closed(window, now))
+    }
+
+    // Every pending count, whether its window has closed or not. The windows
+    // themselves stay, so an open one keeps limiting its site.
+    pub(crate) fn drain(&self) -> Vec<Suppressed> {
+        if self.pending.load(Ordering::Relaxed) == 0 {
+            return Vec::new();
+        }
+        self.take(&mut self.lock(), |_| true)
+    }
+
+    fn take(
+        &self,
+        windows: &mut HashMap<(String, u32), Window>,
+        matches: impl Fn(&Window) -> bool,
+    ) -> Vec<Suppressed> {
+        if self.pending.load(Ordering::Relaxed) == 0 {
+            return Vec::new();
+        }
+        let mut taken = Vec::new();
+        for ((file, line), window) in windows.iter_mut() {
+            if window.suppressed > 0 && matches(window) {
+                self.pending.fetch_sub(window.suppressed, Ordering::Relaxed);
+                taken.push(Suppressed {
+                    file: file.clone(),
+                    line: *line,
+                    level: window.level,
+                    count: window.suppressed,
+                });
+                window.suppressed = 0;
+            }
+        }
+        taken
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<(String, u32), Window>> {
+        self.windows.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn is_closed(window: &Window, now: DateTime<Utc>) -> bool {
+    now.signed_duration_since(window.started) >= TimeDelta::seconds(1)
+}
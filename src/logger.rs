@@ -2,155 +2,1526 @@
 
 use std::{
     backtrace::Backtrace,
+    borrow::Cow,
+    collections::HashMap,
     env, fmt,
-    fs::{self, File},
-    io::{Read, Write},
-    os::unix::fs::MetadataExt,
+    fs::File,
+    io::{self, Read, Write},
+    panic::{self, Location, PanicHookInfo},
     path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU8, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError, TryLockError,
+    },
+    thread,
+    time::Duration,
 };
 
-use chrono::{self, Utc};
+use chrono::{
+    self, DateTime, Datelike, FixedOffset, Local, NaiveDateTime, NaiveTime, Offset, SecondsFormat,
+    TimeZone, Utc,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+use crate::{
+    audit::{self, ChainError, ChainReport},
+    clock::{Clock, ClockHandle},
+    console::Console,
+    dedup::LastRecord,
+    facade,
+    fallback::Outage,
+    file::{self, LogFile},
+    host::HostInfo,
+    limiter::{Decision, RateLimiter, Suppressed},
+    pattern::Pattern,
+    record::{self, truncate, LogValue, Record, ThreadInfo},
+    redact::RedactPatterns,
+    sink::{Layout, Lines, Sink},
+    size,
+    stats::{Counters, LoggerStats},
+    worker::{Message, Worker},
+};
+
+/// Declared in severity order, which is what comparisons use. Off is only a
+/// filter level: as a threshold it silences everything, and records are never
+/// logged at it.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum LogLevel {
-    Debug = 0,
-    Info = 1,
-    Warn = 2,
-    Error = 3,
-    Critical = 4,
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Critical,
+    Off,
+}
+
+impl LogLevel {
+    // Inverse of `level as u8`, used for the stored level.
+    fn from_index(index: u8) -> Option<Self> {
+        const ALL: [LogLevel; 7] = [
+            LogLevel::Trace,
+            LogLevel::Debug,
+            LogLevel::Info,
+            LogLevel::Warn,
+            LogLevel::Error,
+            LogLevel::Critical,
+            LogLevel::Off,
+        ];
+        ALL.get(usize::from(index)).copied()
+    }
 }
 
 impl fmt::Display for LogLevel {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            LogLevel::Trace => write!(f, "TRACE"),
             LogLevel::Debug => write!(f, "DEBUG"),
             LogLevel::Info => write!(f, "INFO"),
             LogLevel::Warn => write!(f, "WARN"),
             LogLevel::Error => write!(f, "ERROR"),
             LogLevel::Critical => write!(f, "CRITICAL"),
+            LogLevel::Off => write!(f, "OFF"),
         }
     }
 }
 
-impl LogLevel {
-    fn from_u64(num: u64) -> Result<Self, ()> {
+// 0..=4 predate Trace and Off and keep their meaning, so the new levels take
+// the next numbers instead.
+impl TryFrom<u64> for LogLevel {
+    type Error = ParseLevelError;
+
+    fn try_from(num: u64) -> Result<Self, ParseLevelError> {
         match num {
             0 => Ok(Self::Debug),
             1 => Ok(Self::Info),
             2 => Ok(Self::Warn),
             3 => Ok(Self::Error),
             4 => Ok(Self::Critical),
-            _ => Err(()),
+            5 => Ok(Self::Trace),
+            6 => Ok(Self::Off),
+            _ => Err(ParseLevelError(num.to_string())),
+        }
+    }
+}
+
+// Accepts level names in any case as well as their numeric values, so
+// `level.to_string().parse()` always round-trips.
+impl FromStr for LogLevel {
+    type Err = ParseLevelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(num) = s.parse::<u64>() {
+            return Self::try_from(num);
+        }
+        match s.to_ascii_lowercase().as_str() {
+            "trace" => Ok(Self::Trace),
+            "debug" => Ok(Self::Debug),
+            "info" => Ok(Self::Info),
+            "warn" => Ok(Self::Warn),
+            "error" => Ok(Self::Error),
+            "critical" => Ok(Self::Critical),
+            "off" => Ok(Self::Off),
+            _ => Err(ParseLevelError(s.to_string())),
+        }
+    }
+}
+
+/// Returned when a level name or number isn't one of the known levels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLevelError(String);
+
+impl fmt::Display for ParseLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unknown log level {:?}, expected one of trace, debug, info, warn, error, critical, off or 0..=6",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseLevelError {}
+
+/// What went wrong creating or writing to the log output, with the path involved.
+#[derive(Debug)]
+pub enum LoggerError {
+    CurrentDir(io::Error),
+    CreateDir {
+        path: PathBuf,
+        source: io::Error,
+    },
+    CreateFile {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Write {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Writer(io::Error),
+    /// Another logger is writing to the directory with the same prefix.
+    Locked {
+        path: PathBuf,
+    },
+    /// The async mode writer thread couldn't be started.
+    SpawnWriter(io::Error),
+}
+
+impl fmt::Display for LoggerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoggerError::CurrentDir(e) => write!(f, "Could not read current directory: {}", e),
+            LoggerError::CreateDir { path, source } => {
+                write!(
+                    f,
+                    "Could not create log directory {}: {}",
+                    path.display(),
+                    source
+                )
+            }
+            LoggerError::CreateFile { path, source } => {
+                write!(
+                    f,
+                    "Could not create log file {}: {}",
+                    path.display(),
+                    source
+                )
+            }
+            LoggerError::Write { path, source } => {
+                write!(
+                    f,
+                    "Could not write to log file {}: {}",
+                    path.display(),
+                    source
+                )
+            }
+            LoggerError::Writer(e) => write!(f, "Could not write to log writer: {}", e),
+            LoggerError::Locked { path } => {
+                write!(f, "Log file {} is locked by another logger", path.display())
+            }
+            LoggerError::SpawnWriter(e) => write!(f, "Could not spawn log writer thread: {}", e),
         }
     }
 }
 
+impl std::error::Error for LoggerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoggerError::CurrentDir(source)
+            | LoggerError::CreateDir { source, .. }
+            | LoggerError::CreateFile { source, .. }
+            | LoggerError::Write { source, .. }
+            | LoggerError::Writer(source)
+            | LoggerError::SpawnWriter(source) => Some(source),
+            LoggerError::Locked { .. } => None,
+        }
+    }
+}
+
+/// A handle to a logger. Clones are cheap and share the same output, level and
+/// statistics, so one logger can be passed to every thread.
+#[derive(Debug, Clone)]
+pub struct Logger {
+    inner: Arc<LoggerInner>,
+    // Only set in async mode. Kept outside LoggerInner because the writer thread
+    // holds its own reference to the inner state.
+    worker: Option<Arc<Worker>>,
+    // Bound by with_fields and added to every record this handle writes.
+    fields: Arc<[(String, LogValue)]>,
+}
+
+#[derive(Debug)]
+pub(crate) struct LoggerInner {
+    config: LogConfig,
+    // The text pattern actually used, see Pattern::for_text.
+    pattern: Pattern,
+    host: HostInfo,
+    // The default level, kept outside the config so set_level works through a
+    // shared reference while other threads are logging.
+    level: AtomicU8,
+    // Set once this logger backs the `log` facade, whose max level then has to
+    // follow set_level.
+    global: AtomicBool,
+    // Everything that changes while logging lives behind one lock, so a record
+    // is always written (and rotated) as a single unit.
+    state: Mutex<LogState>,
+    stats: Counters,
+    limiter: RateLimiter,
+    // Only used in dedup mode.
+    last_record: Mutex<Option<LastRecord>>,
+}
+
+impl LoggerInner {
+    fn state(&self) -> MutexGuard<'_, LogState> {
+        // A panic while holding the lock can't leave a half-written record behind,
+        // so a poisoned lock is still safe to keep using.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn now(&self) -> DateTime<Utc> {
+        self.config.clock.now()
+    }
+
+    // Redaction and truncation happen here, before the line exists, so neither
+    // the output nor the console mirror ever sees the original text. Redacting
+    // first means a cut can't leave part of a secret unmatched.
+    fn format_record(&self, record: &Record) -> Lines {
+        let config = &self.config;
+        if config.redact_patterns.is_empty()
+            && config.redact_keys.is_empty()
+            && config.max_message_len.is_none()
+            && config.max_field_len.is_none()
+        {
+            return self.layout_lines(record);
+        }
+        let patterns = &config.redact_patterns;
+        let mut message = patterns.redact(record.message);
+        if let Some(max_len) = config.max_message_len {
+            self.stats.add_truncated(truncate(&mut message, max_len));
+        }
+        let fields: Vec<(&str, LogValue)> = record
+            .fields
+            .iter()
+            .map(|(key, value)| {
+                let value = match patterns.redact_field(&config.redact_keys, key, value) {
+                    LogValue::Str(text) => match config.max_field_len {
+                        Some(max_len) => {
+                            let mut text = Cow::Owned(text);
+                            self.stats.add_truncated(truncate(&mut text, max_len));
+                            LogValue::Str(text.into_owned())
+                        }
+                        None => LogValue::Str(text),
+                    },
+                    other => other,
+                };
+                (*key, value)
+            })
+            .collect();
+        let error_chain: Vec<String> = record
+            .error_chain
+            .iter()
+            .map(|layer| patterns.redact(layer).into_owned())
+            .collect();
+        self.layout_lines(&Record {
+            message: &message,
+            fields: &fields,
+            error_chain: &error_chain,
+            ..*record
+        })
+    }
+
+    // Sinks sharing the main format reuse its line.
+    fn layout_lines(&self, record: &Record) -> Lines {
+        let main_format = self.config.format;
+        let main = self.layout_record(record, main_format);
+        let sinks = self
+            .config
+            .sinks
+            .iter()
+            .map(|sink| {
+                if !sink.accepts(record.level) {
+                    return None;
+                }
+                Some(match sink.layout() {
+                    Layout::Syslog(syslog) => syslog.format(record, &self.host),
+                    #[cfg(all(target_os = "linux", feature = "journald"))]
+                    Layout::Journald => crate::journald::format(record),
+                    Layout::Format(format) if *format != main_format => {
+                        self.layout_record(record, *format)
+                    }
+                    Layout::Main | Layout::Format(_) => main.clone(),
+                })
+            })
+            .collect();
+        Lines {
+            main,
+            sinks,
+            rotates: true,
+        }
+    }
+
+    fn layout_record(&self, record: &Record, format: LogFormat) -> String {
+        let config = &self.config;
+        let host = (config.host_info || matches!(format, LogFormat::Text | LogFormat::Gelf))
+            .then_some(&self.host);
+        let timezone = config.timezone;
+        let multiline = config.multiline;
+        let csv_fields = &config.csv_fields;
+        if !config.thread_info {
+            let record = Record {
+                host,
+                timezone,
+                multiline,
+                csv_fields,
+                ..*record
+            };
+            return record.format(format, config.timestamp_precision, &self.pattern);
+        }
+        let current = thread::current();
+        let record = Record {
+            thread: Some(ThreadInfo::of(&current)),
+            host,
+            timezone,
+            multiline,
+            csv_fields,
+            ..*record
+        };
+        record.format(format, config.timestamp_precision, &self.pattern)
+    }
+
+    pub(crate) fn write_formatted(&self, level: LogLevel, lines: &Lines, now: DateTime<Utc>) {
+        self.write_locked(&mut self.state(), level, lines, now);
+    }
+
+    // For the panic hook, which can run while this very thread holds the lock
+    // (e.g. a panic inside a custom writer). Waits briefly for other threads to
+    // let go, then gives up and returns false instead of deadlocking.
+    fn try_write_formatted(&self, level: LogLevel, lines: &Lines, now: DateTime<Utc>) -> bool {
+        for _ in 0..100 {
+            match self.state.try_lock() {
+                Ok(mut state) => {
+                    self.write_locked(&mut state, level, lines, now);
+                    return true;
+                }
+                Err(TryLockError::Poisoned(e)) => {
+                    self.write_locked(&mut e.into_inner(), level, lines, now);
+                    return true;
+                }
+                Err(TryLockError::WouldBlock) => thread::sleep(Duration::from_millis(1)),
+            }
+        }
+        false
+    }
+
+    fn write_locked(
+        &self,
+        state: &mut LogState,
+        level: LogLevel,
+        lines: &Lines,
+        now: DateTime<Utc>,
+    ) {
+        self.write_output(state, &lines.main, lines.rotates, now);
+        // Errors are ignored: there is nowhere left to report a failing stderr.
+        if let Some(console) = &mut state.console {
+            let _ = console.write_record(level, &lines.main);
+        }
+        // Each sink is independent, so one failing doesn't hold up the others.
+        for (sink, line) in self.config.sinks.iter().zip(&lines.sinks) {
+            if let Some(line) = line {
+                match sink.write_record(level, line) {
+                    Ok(()) => self.stats.add_bytes(line.len()),
+                    Err(_) => self.stats.record_error(),
+                }
+            }
+        }
+
+        state.unflushed += 1;
+        // Anything at Error or above is flushed right away regardless of policy.
+        let flush = level >= LogLevel::Error
+            || match self.config.flush_policy {
+                FlushPolicy::EveryRecord => true,
+                FlushPolicy::EveryRecords(n) => state.unflushed >= n,
+                FlushPolicy::Interval(interval) => {
+                    now.signed_duration_since(state.last_flush)
+                        .to_std()
+                        .unwrap_or_default()
+                        >= interval
+                }
+            };
+        if flush {
+            self.flush_locked(state, now);
+        }
+    }
+
+    fn write_output(&self, state: &mut LogState, record: &str, rotates: bool, now: DateTime<Utc>) {
+        if let Some(outage) = state.outage {
+            if now < outage.retry_at {
+                return self.fall_back(state, record.as_bytes());
+            }
+            if let Err(e) = self.restore_output(state, now) {
+                self.output_failed(state, e, now);
+                return self.fall_back(state, record.as_bytes());
+            }
+        }
+        if let Output::File(log_file) = &mut state.output {
+            if log_file.is_deleted() {
+                match log_file.reopen(&self.config, now) {
+                    Ok(pending) if !pending.is_empty() => self.fall_back(state, &pending),
+                    Ok(_) => {}
+                    Err(e) => {
+                        self.output_failed(state, e, now);
+                        return self.fall_back(state, record.as_bytes());
+                    }
+                }
+            }
+        }
+        let rolled = match &mut state.output {
+            Output::File(log_file) if rotates => {
+                log_file.check_and_update_rolling(record.len() as u64, now, &self.config)
+            }
+            Output::File(_) | Output::Writer(_) => Ok(()),
+        };
+        // The record still goes into the current file, so this isn't an outage.
+        if let Err(e) = rolled {
+            self.stats.record_rotation_error();
+            state.last_error = Some(e);
+        }
+        match state.output.write_record(record) {
+            Ok(()) => self.stats.add_bytes(record.len()),
+            Err(e) => {
+                self.output_failed(state, e, now);
+                self.fall_back(state, record.as_bytes());
+            }
+        }
+    }
+
+    // Logging must never take the program down, so a failing output sends
+    // records to stderr until a retry succeeds.
+    fn output_failed(&self, state: &mut LogState, error: LoggerError, now: DateTime<Utc>) {
+        self.stats.record_error();
+        state.last_error = Some(error);
+        match &mut state.outage {
+            Some(outage) => outage.retry_failed(now),
+            None => state.outage = Some(Outage::new(now)),
+        }
+    }
+
+    fn fall_back(&self, state: &mut LogState, record: &[u8]) {
+        self.stats.record_fallback();
+        if let Some(outage) = &mut state.outage {
+            outage.records += 1;
+        }
+        // The console mirror already puts every record on stderr.
+        if state.console.is_none() {
+            let _ = io::stderr().write_all(record);
+        }
+    }
+
+    // Files whose handle still fails are reopened by path. Once the output works
+    // again, a single record says how long it was down.
+    fn restore_output(&self, state: &mut LogState, now: DateTime<Utc>) -> Result<(), LoggerError> {
+        if let Err(e) = state.output.flush() {
+            let Output::File(log_file) = &mut state.output else {
+                return Err(e);
+            };
+            let pending = log_file.reopen(&self.config, now)?;
+            if !pending.is_empty() {
+                self.fall_back(state, &pending);
+            }
+        }
+        let Some(outage) = state.outage else {
+            return Ok(());
+        };
+        let summary = outage.summary(now);
+        let record = Record::new(now, LogLevel::Warn, &summary, file!(), line!(), &[]);
+        let line = self.format_record(&record).main;
+        state.output.write_record(&line)?;
+        state.output.flush()?;
+        self.stats.add_bytes(line.len());
+        state.outage = None;
+        Ok(())
+    }
+
+    fn flush_locked(&self, state: &mut LogState, now: DateTime<Utc>) {
+        // A failing output is left alone until its next retry.
+        if state.outage.is_none() {
+            if let Err(e) = state.output.flush() {
+                self.output_failed(state, e, now);
+            }
+        }
+        if let Some(console) = &mut state.console {
+            let _ = console.flush();
+        }
+        for sink in &self.config.sinks {
+            if sink.flush().is_err() {
+                self.stats.record_error();
+            }
+        }
+        state.unflushed = 0;
+        state.last_flush = now;
+    }
+
+    pub(crate) fn flush_output(&self) {
+        self.flush_locked(&mut self.state(), self.now());
+    }
+
+    // Holds the lock throughout, so concurrent records land whole in either the
+    // old file or the new one.
+    pub(crate) fn reopen(&self) -> Result<(), LoggerError> {
+        let now = self.now();
+        let mut state = self.state();
+        let Output::File(log_file) = &mut state.output else {
+            return Ok(());
+        };
+        match self.config.reopen_mode {
+            ReopenMode::SamePath => {
+                // Anything the old handle couldn't flush goes to stderr rather than
+                // being lost.
+                let _ = log_file.flush();
+                let pending = log_file.reopen(&self.config, now)?;
+                if !pending.is_empty() {
+                    self.fall_back(&mut state, &pending);
+                }
+            }
+            ReopenMode::NewFile => log_file.roll(&self.config, now)?,
+        }
+        Ok(())
+    }
+}
+
+impl Drop for LoggerInner {
+    fn drop(&mut self) {
+        let now = self.now();
+        for site in self.limiter.drain() {
+            let summary = site.summary();
+            let record = Record::new(now, site.level, &summary, &site.file, site.line, &[]);
+            self.write_formatted(site.level, &self.format_record(&record), now);
+        }
+        let pending = self
+            .last_record
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(last) = pending.filter(|last| last.repeated > 0) {
+            let summary = last.summary();
+            let record = Record::new(now, last.level, &summary, &last.file, last.line, &[]);
+            let lines = Lines {
+                rotates: false,
+                ..self.format_record(&record)
+            };
+            self.write_formatted(last.level, &lines, now);
+        }
+        self.flush_output();
+    }
+}
+
+#[derive(Debug)]
+struct LogState {
+    output: Output,
+    console: Option<Console>,
+    last_error: Option<LoggerError>,
+    unflushed: usize,
+    last_flush: DateTime<Utc>,
+    // Set while the output is failing.
+    outage: Option<Outage>,
+}
+
+enum Output {
+    File(LogFile),
+    // A caller-supplied destination; rotation settings don't apply to it.
+    Writer(Box<dyn Write + Send>),
+}
+
+impl fmt::Debug for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Output::File(log_file) => f.debug_tuple("File").field(log_file).finish(),
+            Output::Writer(_) => f.write_str("Writer"),
+        }
+    }
+}
+
+impl Output {
+    fn write_record(&mut self, record: &str) -> Result<(), LoggerError> {
+        match self {
+            Output::File(log_file) => log_file.write_record(record),
+            Output::Writer(writer) => writer
+                .write_all(record.as_bytes())
+                .map_err(LoggerError::Writer),
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), LoggerError> {
+        match self {
+            Output::File(log_file) => log_file.flush(),
+            Output::Writer(writer) => writer.flush().map_err(LoggerError::Writer),
+        }
+    }
+
+    fn path(&self) -> Option<&Path> {
+        match self {
+            Output::File(log_file) => Some(&log_file.path),
+            Output::Writer(_) => None,
+        }
+    }
+}
+
+impl Logger {
+    /// Like try_new, but panics if the logger can't be created.
+    pub fn new(config: Option<LogConfig>) -> Self {
+        Self::try_new(config).expect("Could not create logger")
+    }
+
+    /// Creates the logs directory and the first log file. `None` uses
+    /// LogConfig::default.
+    pub fn try_new(config: Option<LogConfig>) -> Result<Self, LoggerError> {
+        let mut config = config.unwrap_or_default();
+        // Resolve once so a later change of working directory doesn't move the logs.
+        if config.directory.is_relative() {
+            config.directory = env::current_dir()
+                .map_err(LoggerError::CurrentDir)?
+                .join(&config.directory);
+        }
+        let now = config.clock.now();
+        if config.lock_conflict == LockConflict::Error {
+            file::check_unlocked(&config)?;
+        }
+        // An aligned logger started mid-period carries on with that period's file.
+        let reuse =
+            config.reuse_latest || config.rolling_config.time_alignment == TimeAlignment::Aligned;
+        let reused = reuse.then(|| LogFile::reuse_latest(&config, now)).flatten();
+        let log_file = match reused {
+            Some(log_file) => log_file,
+            None => LogFile::create(&config, now)?,
+        };
+        if config.latest_symlink {
+            log_file.update_latest_link(&config.file_prefix);
+        }
+        Self::with_output(config, Output::File(log_file))
+    }
+
+    /// Like try_with_writer, but panics if the async writer thread can't start.
+    pub fn with_writer(config: Option<LogConfig>, writer: Box<dyn Write + Send>) -> Self {
+        Self::try_with_writer(config, writer).expect("Could not create logger")
+    }
+
+    /// Writes to `writer` instead of a file under the logs directory.
+    pub fn try_with_writer(
+        config: Option<LogConfig>,
+        mut writer: Box<dyn Write + Send>,
+    ) -> Result<Self, LoggerError> {
+        let config = config.unwrap_or_default();
+        // A failed header write isn't reported here; the first record will
+        // most likely fail the same way.
+        if let Some(header) = config.csv_header() {
+            let _ = writer.write_all(header.as_bytes());
+        }
+        Self::with_output(config, Output::Writer(writer))
+    }
+
+    fn with_output(config: LogConfig, output: Output) -> Result<Self, LoggerError> {
+        let console = config.console.then(|| Console::stderr(config.color));
+        // Sinks writing CSV start with the header row, like the main output.
+        // As there, a failed write is left for the first record to report.
+        let csv_sinks = config
+            .sinks
+            .iter()
+            .filter(|sink| sink.format(config.format) == Some(LogFormat::Csv));
+        for sink in csv_sinks {
+            let _ = sink.write_record(LogLevel::Info, &record::csv_header(&config.csv_fields));
+        }
+        let async_options = config
+            .async_mode
+            .then_some((config.buffer_capacity, config.overflow_policy));
+        let now = config.clock.now();
+        let inner = Arc::new(LoggerInner {
+            pattern: config
+                .pattern
+                .for_text(config.thread_info, config.host_info),
+            host: HostInfo::detect(config.instance_name.as_deref()),
+            level: AtomicU8::new(config.level as u8),
+            global: AtomicBool::new(false),
+            config,
+            state: Mutex::new(LogState {
+                output,
+                console,
+                last_error: None,
+                unflushed: 0,
+                last_flush: now,
+                outage: None,
+            }),
+            stats: Counters::default(),
+            limiter: RateLimiter::default(),
+            last_record: Mutex::new(None),
+        });
+        let worker = match async_options {
+            Some((capacity, policy)) => Some(Arc::new(
+                Worker::spawn(Arc::clone(&inner), capacity, policy)
+                    .map_err(LoggerError::SpawnWriter)?,
+            )),
+            None => None,
+        };
+        Ok(Self {
+            inner,
+            worker,
+            fields: Arc::new([]),
+        })
+    }
+
+    fn state(&self) -> MutexGuard<'_, LogState> {
+        self.inner.state()
+    }
+
+    /// None when logging to a custom writer.
+    pub fn current_file_path(&self) -> Option<PathBuf> {
+        self.state().output.path().map(Path::to_path_buf)
+    }
+
+    /// Write and rotation failures don't panic; the most recent one is kept until
+    /// taken.
+    pub fn take_error(&self) -> Option<LoggerError> {
+        self.state().last_error.take()
+    }
+
+    /// Whether a record at `level` logged from here would be written, e.g. to
+    /// skip building an expensive payload. It checks the target's own level when
+    /// there is one, and the rate limit of the calling file and line, without
+    /// using up any of it.
+    #[track_caller]
+    pub fn enabled(&self, level: LogLevel, target: Option<&str>) -> bool {
+        self.level_enabled(level, target) && self.within_rate_limit(Location::caller())
+    }
+
+    /// Like enabled, with the level configured for `target` or its closest
+    /// parent module.
+    #[track_caller]
+    pub fn enabled_for(&self, level: LogLevel, target: &str) -> bool {
+        self.level_enabled_for(level, target) && self.within_rate_limit(Location::caller())
+    }
+
+    fn level_enabled(&self, level: LogLevel, target: Option<&str>) -> bool {
+        match target {
+            Some(target) => self.level_enabled_for(level, target),
+            None => level != LogLevel::Off && level >= self.level(),
+        }
+    }
+
+    pub(crate) fn level_enabled_for(&self, level: LogLevel, target: &str) -> bool {
+        let threshold = match self.inner.config.level_for(target) {
+            Some(target_level) => target_level,
+            None => self.level(),
+        };
+        level != LogLevel::Off && level >= threshold
+    }
+
+    fn within_rate_limit(&self, location: &Location) -> bool {
+        self.inner.config.rate_limit.is_none_or(|per_second| {
+            self.inner.limiter.has_room(
+                location.file(),
+                location.line(),
+                per_second,
+                self.inner.now(),
+            )
+        })
+    }
+
+    /// Used by the macros and the `log` facade; unlike enabled_for, a record that
+    /// fails the check is counted in the filtered stats.
+    #[doc(hidden)]
+    pub fn should_log(&self, level: LogLevel, target: &str) -> bool {
+        self.count_filtered(self.level_enabled_for(level, target), level)
+    }
+
+    fn count_filtered(&self, enabled: bool, level: LogLevel) -> bool {
+        if !enabled {
+            self.inner.stats.record_filtered(level);
+        }
+        enabled
+    }
+
+    /// A snapshot of the counters shared by every handle of this logger.
+    pub fn stats(&self) -> LoggerStats {
+        let sink_dropped = self.inner.config.sinks.iter().map(Sink::dropped).sum();
+        self.inner.stats.snapshot(sink_dropped)
+    }
+
+    // The most verbose level any target can log at.
+    pub(crate) fn min_level(&self) -> LogLevel {
+        self.inner
+            .config
+            .target_levels
+            .values()
+            .copied()
+            .fold(self.level(), LogLevel::min)
+    }
+
+    /// The default level, as last set by set_level.
+    pub fn level(&self) -> LogLevel {
+        let level = self.inner.level.load(Ordering::Relaxed);
+        LogLevel::from_index(level).expect("stored level is valid")
+    }
+
+    /// Changes the default level for every handle sharing this logger. Per-target
+    /// levels are left alone.
+    pub fn set_level(&self, level: LogLevel) {
+        self.inner.level.store(level as u8, Ordering::Relaxed);
+        if self.inner.global.load(Ordering::Relaxed) {
+            facade::update_max_level(self);
+        }
+    }
+
+    /// Writes `message` at `log_level` if that level is enabled.
+    #[track_caller]
+    pub fn log(&self, log_level: LogLevel, message: &str) {
+        self.log_kv(log_level, message, &[]);
+    }
+
+    /// Only calls `message` if the level is enabled and the call site isn't over
+    /// its rate limit.
+    #[track_caller]
+    pub fn log_with(&self, log_level: LogLevel, message: impl FnOnce() -> String) {
+        if self.count_filtered(self.level_enabled(log_level, None), log_level) {
+            self.write_lazy(log_level, None, Location::caller(), message);
+        }
+    }
+
+    /// log_with checked against the level of `target`, like log_target.
+    #[track_caller]
+    pub fn log_target_with(
+        &self,
+        log_level: LogLevel,
+        target: &str,
+        message: impl FnOnce() -> String,
+    ) {
+        if self.should_log(log_level, target) {
+            self.write_lazy(log_level, Some(target), Location::caller(), message);
+        }
+    }
+
+    /// Like log, with structured fields after the message.
+    #[track_caller]
+    pub fn log_kv(&self, log_level: LogLevel, message: &str, fields: &[(&str, LogValue)]) {
+        if self.count_filtered(self.level_enabled(log_level, None), log_level) {
+            let location = Location::caller();
+            self.write(
+                log_level,
+                None,
+                message,
+                location.file(),
+                location.line(),
+                fields,
+            );
+        }
+    }
+
+    /// Plain methods have no target and only see the global level; the macros
+    /// pass the caller's module path.
+    #[track_caller]
+    pub fn log_target(
+        &self,
+        log_level: LogLevel,
+        target: &str,
+        message: &str,
+        fields: &[(&str, LogValue)],
+    ) {
+        if self.should_log(log_level, target) {
+            self.log_checked(log_level, target, message, fields);
+        }
+    }
+
+    // Used by log! once should_log has passed, so the target lookup isn't done
+    // twice.
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn log_checked(
+        &self,
+        log_level: LogLevel,
+        target: &str,
+        message: &str,
+        fields: &[(&str, LogValue)],
+    ) {
+        let location = Location::caller();
+        self.write(
+            log_level,
+            Some(target),
+            message,
+            location.file(),
+            location.line(),
+            fields,
+        );
+    }
+
+    /// Logs `error` and everything behind its source() as one record:
+    /// `error: <top> caused by: <mid> caused by: <root>`. JSON output also gets the
+    /// layers, outermost first, as an `error_chain` array.
+    #[track_caller]
+    pub fn log_error_chain(&self, log_level: LogLevel, error: &dyn std::error::Error) {
+        if !self.count_filtered(self.level_enabled(log_level, None), log_level) {
+            return;
+        }
+        let layers: Vec<String> = std::iter::successors(Some(error), |e| e.source())
+            .map(|e| {
+                if self.inner.config.error_debug {
+                    format!("{} ({:?})", e, e)
+                } else {
+                    e.to_string()
+                }
+            })
+            .collect();
+        let message = format!("error: {}", layers.join(" caused by: "));
+        let location = Location::caller();
+        let record = Record {
+            error_chain: &layers,
+            ..Record::new(
+                self.inner.now(),
+                log_level,
+                &message,
+                location.file(),
+                location.line(),
+                &[],
+            )
+        };
+        self.write_limited(record, self.inner.config.rate_limit);
+    }
+
+    /// log_error_chain at Error.
+    #[track_caller]
+    pub fn error_chain(&self, error: &dyn std::error::Error) {
+        self.log_error_chain(LogLevel::Error, error);
+    }
+
+    /// log_error_chain at Critical.
+    #[track_caller]
+    pub fn critical_chain(&self, error: &dyn std::error::Error) {
+        self.log_error_chain(LogLevel::Critical, error);
+    }
+
+    /// Returns a handle writing to the same output that stamps `fields` on every
+    /// record. Fields bound here replace inherited ones with the same key, and
+    /// fields passed at the call site replace both.
+    pub fn with_fields(&self, fields: &[(&str, LogValue)]) -> Logger {
+        let bound = merge_fields(&self.fields, fields)
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+        Logger {
+            inner: Arc::clone(&self.inner),
+            worker: self.worker.clone(),
+            fields: bound,
+        }
+    }
+
+    // Used by the *_limited! macros after should_log: at most `per_second`
+    // records from this call site are written each second, whatever
+    // with_rate_limit says.
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn log_limited(
+        &self,
+        log_level: LogLevel,
+        target: &str,
+        per_second: u32,
+        message: &str,
+        fields: &[(&str, LogValue)],
+    ) {
+        let location = Location::caller();
+        let record = Record {
+            target: Some(target),
+            ..Record::new(
+                self.inner.now(),
+                log_level,
+                message,
+                location.file(),
+                location.line(),
+                fields,
+            )
+        };
+        self.write_limited(record, Some(per_second));
+    }
+
+    pub(crate) fn write(
+        &self,
+        log_level: LogLevel,
+        target: Option<&str>,
+        message: &str,
+        file: &str,
+        line: u32,
+        fields: &[(&str, LogValue)],
+    ) {
+        let record = Record {
+            target,
+            ..Record::new(self.inner.now(), log_level, message, file, line, fields)
+        };
+        self.write_limited(record, self.inner.config.rate_limit);
+    }
+
+    // The rate limit is checked before `message` is built, so a throttled call
+    // site doesn't pay for it.
+    fn write_lazy(
+        &self,
+        log_level: LogLevel,
+        target: Option<&str>,
+        location: &Location,
+        message: impl FnOnce() -> String,
+    ) {
+        let now = self.inner.now();
+        let (file, line) = (location.file(), location.line());
+        if !self.admit(file, line, log_level, self.inner.config.rate_limit, now) {
+            return;
+        }
+        let message = message();
+        self.write_unlimited(Record {
+            target,
+            ..Record::new(now, log_level, &message, file, line, &[])
+        });
+    }
+
+    fn write_limited(&self, record: Record, per_second: Option<u32>) {
+        if self.admit(
+            record.file,
+            record.line,
+            record.level,
+            per_second,
+            record.timestamp,
+        ) {
+            self.write_unlimited(record);
+        }
+    }
+
+    // Whether the call site is within its limit, reporting any windows that
+    // have closed first.
+    fn admit(
+        &self,
+        file: &str,
+        line: u32,
+        level: LogLevel,
+        per_second: Option<u32>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let Some(per_second) = per_second else {
+            self.report_suppressed(self.inner.limiter.closed(now), now);
+            return true;
+        };
+        let (decision, closed) = self.inner.limiter.check(file, line, level, per_second, now);
+        self.report_suppressed(closed, now);
+        matches!(decision, Decision::Allow)
+    }
+
+    fn report_suppressed(&self, suppressed: Vec<Suppressed>, now: DateTime<Utc>) {
+        for site in suppressed {
+            let summary = site.summary();
+            self.write_unlimited(Record::new(
+                now,
+                site.level,
+                &summary,
+                &site.file,
+                site.line,
+                &[],
+            ));
+        }
+    }
+
+    fn write_unlimited(&self, record: Record) {
+        let merged;
+        let record = if self.fields.is_empty() {
+            record
+        } else {
+            merged = merge_fields(&self.fields, record.fields);
+            Record {
+                fields: &merged,
+                ..record
+            }
+        };
+        self.inner.stats.record_written(record.level);
+        if !self.inner.config.dedup {
+            self.emit(&record);
+            return;
+        }
+
+        // The lock is held while writing so the summary and the record that ends
+        // a run can't be reordered by another thread.
+        let now = record.timestamp;
+        let mut last_record = self.last_record();
+        if let Some(last) = last_record.as_mut().filter(|last| last.matches(&record)) {
+            last.repeated += 1;
+            let elapsed = now
+                .signed_duration_since(last.since)
+                .to_std()
+                .unwrap_or_default();
+            if elapsed >= self.inner.config.dedup_interval {
+                self.emit_repeats(last, now);
+            }
+            return;
+        }
+        if let Some(last) = last_record.as_mut() {
+            self.emit_repeats(last, now);
+        }
+        *last_record = Some(LastRecord::new(&record, now));
+        self.emit(&record);
+    }
+
+    fn last_record(&self) -> MutexGuard<'_, Option<LastRecord>> {
+        self.inner
+            .last_record
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Called before the record that ends a run, so the summary is written and
+    // any rotation that record causes comes after it.
+    fn emit_repeats(&self, last: &mut LastRecord, now: DateTime<Utc>) {
+        if last.repeated > 0 {
+            let summary = last.summary();
+            let record = Record::new(now, last.level, &summary, &last.file, last.line, &[]);
+            let lines = Lines {
+                rotates: false,
+                ..self.inner.format_record(&record)
+            };
+            self.send(last.level, lines, now);
+            last.repeated = 0;
+            last.since = now;
+        }
+    }
+
+    fn emit(&self, record: &Record) {
+        self.send(
+            record.level,
+            self.inner.format_record(record),
+            record.timestamp,
+        );
+    }
+
+    fn send(&self, level: LogLevel, lines: Lines, now: DateTime<Utc>) {
+        match &self.worker {
+            Some(worker) => worker.send(Message::Record { level, lines, now }),
+            None => self.inner.write_formatted(level, &lines, now),
+        }
+    }
+
+    /// In async mode this waits for the writer thread to catch up first. Pending
+    /// rate limit and dedup summaries are reported before flushing.
+    pub fn flush(&self) {
+        let now = self.inner.now();
+        self.report_suppressed(self.inner.limiter.drain(), now);
+        if let Some(last) = self.last_record().as_mut() {
+            self.emit_repeats(last, now);
+        }
+        match &self.worker {
+            Some(worker) => worker.flush(),
+            None => self.inner.flush_output(),
+        }
+    }
+
+    /// Closes the log file and opens it again, e.g. after an external tool moved
+    /// it. Writers are unaffected, and does nothing for custom writers.
+    pub fn reopen(&self) -> Result<(), LoggerError> {
+        self.inner.reopen()
+    }
+
+    /// Recomputes the hash chain of a file written in audit mode and reports the
+    /// first line that doesn't match. Follow `next_file` to check a whole series.
+    pub fn verify_file(path: &Path) -> Result<ChainReport, ChainError> {
+        audit::verify_file(path)
+    }
+
+    /// Calls reopen whenever the process receives SIGHUP, from a background
+    /// thread that stops once the logger is dropped.
+    #[cfg(all(unix, feature = "sighup"))]
+    pub fn install_sighup_handler(&self) -> io::Result<()> {
+        crate::signal::on_sighup(Arc::downgrade(&self.inner))
+    }
+
+    /// Records discarded by the DropOldest overflow policy.
+    pub fn dropped_count(&self) -> u64 {
+        self.worker
+            .as_ref()
+            .map_or(0, |worker| worker.dropped_count())
+    }
+
+    /// Writes panics as Critical records with a backtrace, then runs the hook that
+    /// was installed before. Panic records skip the async queue, rate limiting and
+    /// dedup, and fall back to stderr if the file can't be locked.
+    pub fn install_panic_hook(&self) {
+        let logger = self.clone();
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            logger.log_panic(info);
+            previous(info);
+        }));
+    }
+
+    fn log_panic(&self, info: &PanicHookInfo) {
+        let payload = info
+            .payload()
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("Box<dyn Any>");
+        let thread = thread::current();
+        // Always captured: a panic is exactly when the backtrace is worth the cost,
+        // whatever RUST_BACKTRACE says.
+        let message = format!(
+            "thread '{}' panicked: {}\n{}",
+            thread.name().unwrap_or("unnamed"),
+            payload,
+            Backtrace::force_capture()
+        );
+        let (file, line) = info.location().map_or(("<unknown>", 0), |location| {
+            (location.file(), location.line())
+        });
+
+        let fields = merge_fields(&self.fields, &[]);
+        let record = Record::new(
+            self.inner.now(),
+            LogLevel::Critical,
+            &message,
+            file,
+            line,
+            &fields,
+        );
+        let now = record.timestamp;
+        let lines = self.inner.format_record(&record);
+        if !self
+            .inner
+            .try_write_formatted(LogLevel::Critical, &lines, now)
+        {
+            eprint!("{}", lines.main);
+        }
+    }
+
+    /// Routes records from the `log` crate macros to this logger. Fails if
+    /// another global logger is already installed.
+    pub fn install_global(&self) -> Result<(), log::SetLoggerError> {
+        facade::install(self.clone())?;
+        self.inner.global.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    // Public methods for different log levels
+    #[track_caller]
+    pub fn trace(&self, message: &str) {
+        self.log(LogLevel::Trace, message);
+    }
+
+    #[track_caller]
+    pub fn debug(&self, message: &str) {
+        self.log(LogLevel::Debug, message);
+    }
+
+    #[track_caller]
+    pub fn info(&self, message: &str) {
+        self.log(LogLevel::Info, message);
+    }
+
+    #[track_caller]
+    pub fn warn(&self, message: &str) {
+        self.log(LogLevel::Warn, message);
+    }
+
+    #[track_caller]
+    pub fn error(&self, message: &str) {
+        self.log(LogLevel::Error, message);
+    }
+
+    #[track_caller]
+    pub fn critical(&self, message: &str) {
+        self.log(LogLevel::Critical, message);
+    }
+
+    // Lazy variants for messages that are expensive to build. Like the plain
+    // methods they only see the global level; log_target_with takes a target.
+    #[track_caller]
+    pub fn trace_with(&self, message: impl FnOnce() -> String) {
+        self.log_with(LogLevel::Trace, message);
+    }
+
+    #[track_caller]
+    pub fn debug_with(&self, message: impl FnOnce() -> String) {
+        self.log_with(LogLevel::Debug, message);
+    }
+
+    #[track_caller]
+    pub fn info_with(&self, message: impl FnOnce() -> String) {
+        self.log_with(LogLevel::Info, message);
+    }
+
+    #[track_caller]
+    pub fn warn_with(&self, message: impl FnOnce() -> String) {
+        self.log_with(LogLevel::Warn, message);
+    }
+
+    #[track_caller]
+    pub fn error_with(&self, message: impl FnOnce() -> String) {
+        self.log_with(LogLevel::Error, message);
+    }
+
+    #[track_caller]
+    pub fn critical_with(&self, message: impl FnOnce() -> String) {
+        self.log_with(LogLevel::Critical, message);
+    }
+}
+
+// Keeps the order of `bound`, dropping keys that `fields` overrides.
+fn merge_fields<'a>(
+    bound: &'a [(String, LogValue)],
+    fields: &[(&'a str, LogValue)],
+) -> Vec<(&'a str, LogValue)> {
+    bound
+        .iter()
+        .filter(|(key, _)| fields.iter().all(|(k, _)| k != key))
+        .map(|(key, value)| (key.as_str(), value.clone()))
+        .chain(fields.iter().cloned())
+        .collect()
+}
+
+/// Why a config couldn't be loaded or a setting was rejected.
 #[derive(Debug)]
-pub struct Logger {
-    config: LogConfig,
-    log_file: File,
+pub enum ConfigError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Json(serde_json::Error),
+    #[cfg(feature = "toml")]
+    Toml(toml::de::Error),
+    UnsupportedFormat(PathBuf),
+    WrongType {
+        key: String,
+        expected: &'static str,
+    },
+    OutOfRange {
+        key: String,
+        reason: String,
+    },
+    InvalidEnv {
+        var: String,
+        value: String,
+        reason: String,
+    },
+    InvalidPattern {
+        pattern: String,
+        reason: String,
+    },
+    InvalidUrl {
+        url: String,
+        reason: String,
+    },
+    InvalidRedactPattern {
+        pattern: String,
+        reason: String,
+    },
 }
 
-impl Logger {
-    pub fn new(config: Option<LogConfig>) -> Self {
-        let config = config.unwrap_or_else(LogConfig::new);
-
-
-        let mut path = PathBuf::from(env::current_dir().unwrap());
-        path.push("logs");
-        path.push(format!(
-            "{}{}",
-            &config.file_prefix,
-            &Utc::now()
-                .to_string()
-                .replace(['.', ':'], "-")
-                .replace(' ', "T")
-        ));
-        path.set_extension("log");
-
-        fs::create_dir_all(env::current_dir().unwrap().join("logs")).unwrap();
-        let log_file = File::create(path).unwrap();
-        Self { config, log_file }
-    }
-
-    fn create_new_log_file(&self) -> File{
-        let mut path = PathBuf::from(env::current_dir().unwrap());
-        path.push("logs");
-        path.push(format!(
-            "{}{}",
-            &self.config.file_prefix,
-            &Utc::now()
-                .to_string()
-                .replace(['.', ':'], "-")
-                .replace(' ', "T")
-        ));
-        path.set_extension("log");
-
-        fs::create_dir_all(env::current_dir().unwrap().join("logs")).unwrap();
-        let log_file = File::create(path).unwrap();
-        log_file
-    }
-
-    fn log(&mut self, message: &str, log_level: LogLevel) {
-        if log_level >= self.config.level {
-            self.check_and_update_rolling();
-
-            let bt = Backtrace::force_capture();
-            let caller_name = bt
-                .frames()
-                .iter()
-                .rev()
-                .nth_back(4)
-                .expect("Could not get caller name");
-            self.log_file
-                .write(format!("{}:{:?} {}\n", log_level, caller_name, message).as_bytes())
-                .unwrap();
-        }
-    }
-
-    fn check_and_update_rolling(&mut self) {
-        let meta: fs::Metadata = self.log_file.metadata().unwrap();
-        let file_size = meta.size();
-        let file_birth_time = meta.created().unwrap();
-        let now = std::time::SystemTime::now();
-        let time_difference = now
-            .duration_since(file_birth_time)
-            .expect("Clock may have gone backwards");
-        if file_size >= self.config.rolling_config.size_threshold as u64
-            || time_difference.as_secs() > self.config.rolling_config.time_threshold as u64
-        {
-            self.log_file = self.create_new_log_file();
+impl ConfigError {
+    fn out_of_range(key: &str, reason: impl fmt::Display) -> Self {
+        ConfigError::OutOfRange {
+            key: key.to_string(),
+            reason: reason.to_string(),
         }
     }
 
-    // Public methods for different log levels
-    pub fn debug(&mut self, message: &str) {
-        self.log(message, LogLevel::Debug);
-    }
-
-    pub fn info(&mut self, message: &str) {
-        self.log(message, LogLevel::Info);
+    fn invalid_env(var: &str, value: &str, reason: &str) -> Self {
+        ConfigError::InvalidEnv {
+            var: var.to_string(),
+            value: value.to_string(),
+            reason: reason.to_string(),
+        }
     }
+}
 
-    pub fn warn(&mut self, message: &str) {
-        self.log(message, LogLevel::Warn);
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => {
+                write!(
+                    f,
+                    "Could not read config file {}: {}",
+                    path.display(),
+                    source
+                )
+            }
+            ConfigError::Json(e) => write!(f, "Config file is not valid JSON: {}", e),
+            #[cfg(feature = "toml")]
+            ConfigError::Toml(e) => write!(f, "Config file is not valid TOML: {}", e),
+            ConfigError::UnsupportedFormat(path) => {
+                write!(f, "Unsupported config file format: {}", path.display())
+            }
+            ConfigError::WrongType { key, expected } => {
+                write!(f, "Expected {} to be {}", key, expected)
+            }
+            ConfigError::OutOfRange { key, reason } => {
+                write!(f, "Invalid value for {}: {}", key, reason)
+            }
+            ConfigError::InvalidEnv { var, value, reason } => {
+                write!(f, "Invalid value {:?} for {}: {}", value, var, reason)
+            }
+            ConfigError::InvalidPattern { pattern, reason } => {
+                write!(f, "Invalid log pattern {:?}: {}", pattern, reason)
+            }
+            ConfigError::InvalidUrl { url, reason } => {
+                write!(f, "Invalid sink URL {:?}: {}", url, reason)
+            }
+            ConfigError::InvalidRedactPattern { pattern, reason } => {
+                write!(f, "Invalid redact pattern {:?}: {}", pattern, reason)
+            }
+        }
     }
+}
 
-    pub fn error(&mut self, message: &str) {
-        self.log(message, LogLevel::Error);
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            ConfigError::Json(e) => Some(e),
+            #[cfg(feature = "toml")]
+            ConfigError::Toml(e) => Some(e),
+            _ => None,
+        }
     }
+}
 
-    pub fn critical(&mut self, message: &str) {
-        self.log(message, LogLevel::Critical);
+fn env_var(var: &str) -> Result<Option<String>, ConfigError> {
+    match env::var(var) {
+        Ok(value) => Ok(Some(value)),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(_)) => Err(ConfigError::invalid_env(
+            var,
+            "<non-unicode>",
+            "Expected valid UTF-8",
+        )),
     }
 }
 
-#[derive(Debug)]
+/// Serde uses the same keys as the fields below and rejects unknown ones; missing
+/// keys keep their defaults. The JSON/TOML file loaders are more lenient and
+/// collect unknown keys as warnings instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct LogConfig {
     level: LogLevel,
     rolling_config: RollingConfig,
     file_prefix: String,
+    directory: PathBuf,
+    target_levels: HashMap<String, LogLevel>,
+    timestamp_precision: TimestampPrecision,
+    format: LogFormat,
+    pattern: Pattern,
+    console: bool,
+    color: bool,
+    async_mode: bool,
+    buffer_capacity: usize,
+    overflow_policy: OverflowPolicy,
+    flush_policy: FlushPolicy,
+    reuse_latest: bool,
+    latest_symlink: bool,
+    rate_limit: Option<u32>,
+    dedup: bool,
+    dedup_interval: Duration,
+    error_debug: bool,
+    sync_mode: SyncMode,
+    thread_info: bool,
+    host_info: bool,
+    instance_name: Option<String>,
+    timezone: Timezone,
+    reopen_mode: ReopenMode,
+    redact_patterns: RedactPatterns,
+    redact_keys: Vec<String>,
+    max_message_len: Option<usize>,
+    max_field_len: Option<usize>,
+    multiline: Multiline,
+    csv_fields: Vec<String>,
+    audit: bool,
+    lock_conflict: LockConflict,
+    #[serde(skip)]
+    sinks: Vec<Sink>,
+    #[serde(skip)]
+    clock: ClockHandle,
+    #[serde(skip)]
+    warnings: Vec<String>,
 }
 
 impl LogConfig {
@@ -159,114 +1530,817 @@ impl LogConfig {
             level: LogLevel::Info,
             rolling_config: RollingConfig::new(),
             file_prefix: "Logtar_".to_string(),
+            directory: PathBuf::from("logs"),
+            target_levels: HashMap::new(),
+            timestamp_precision: TimestampPrecision::Millis,
+            format: LogFormat::Text,
+            pattern: Pattern::default(),
+            console: false,
+            color: true,
+            async_mode: false,
+            buffer_capacity: 1024,
+            overflow_policy: OverflowPolicy::Block,
+            flush_policy: FlushPolicy::EveryRecord,
+            reuse_latest: false,
+            latest_symlink: false,
+            rate_limit: None,
+            dedup: false,
+            dedup_interval: Duration::from_secs(30),
+            error_debug: false,
+            sync_mode: SyncMode::None,
+            thread_info: false,
+            host_info: false,
+            instance_name: None,
+            timezone: Timezone::Utc,
+            reopen_mode: ReopenMode::SamePath,
+            redact_patterns: RedactPatterns::default(),
+            redact_keys: Vec::new(),
+            max_message_len: None,
+            max_field_len: None,
+            multiline: Multiline::Raw,
+            csv_fields: Vec::new(),
+            audit: false,
+            lock_conflict: LockConflict::NewFile,
+            sinks: Vec::new(),
+            clock: ClockHandle::default(),
+            warnings: Vec::new(),
         }
     }
 
+    /// The default level; records below it are skipped. Defaults to Info.
     pub fn with_level(mut self, level: LogLevel) -> Self {
         self.level = level;
         self
     }
+    /// When log files are rotated and how many are kept.
     pub fn with_rolling_config(mut self, config: RollingConfig) -> Self {
         self.rolling_config = config;
         self
     }
+    /// Starts every log file name; defaults to "Logtar_".
     pub fn with_file_prefix(mut self, prefix: String) -> Self {
         self.file_prefix = prefix;
         self
     }
+    /// Where log files go; defaults to "logs". Relative paths are resolved
+    /// once, when the logger is created.
+    pub fn with_directory(mut self, directory: PathBuf) -> Self {
+        self.directory = directory;
+        self
+    }
+    /// Overrides the level for a module path and everything below it, e.g.
+    /// "hyper" or "my_app::db".
+    pub fn with_target_level(mut self, target: &str, level: LogLevel) -> Self {
+        self.target_levels.insert(target.to_string(), level);
+        self
+    }
+    /// Defaults to milliseconds.
+    pub fn with_timestamp_precision(mut self, precision: TimestampPrecision) -> Self {
+        self.timestamp_precision = precision;
+        self
+    }
+    /// The layout of the main output; defaults to Text.
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+    /// Layout of text records: %t timestamp, %l level, %f file, %L line, %m message,
+    /// %T thread name, %H host and pid as `host:pid`, and %% for a literal percent
+    /// sign. Fields are appended after it. Defaults to "%t %l [%f:%L] %m".
+    pub fn with_pattern(mut self, pattern: &str) -> Result<Self, ConfigError> {
+        self.pattern = Pattern::parse(pattern).map_err(|reason| ConfigError::InvalidPattern {
+            pattern: pattern.to_string(),
+            reason,
+        })?;
+        Ok(self)
+    }
+    /// Also writes every record to stderr.
+    pub fn with_console(mut self, console: bool) -> Self {
+        self.console = console;
+        self
+    }
+    /// Colors are used on the console only when stderr is a terminal and NO_COLOR
+    /// isn't set, unless turned off here.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+    /// Hands formatted records to a background thread instead of writing them on
+    /// the calling thread.
+    pub fn with_async(mut self, async_mode: bool) -> Self {
+        self.async_mode = async_mode;
+        self
+    }
+    /// How many records the async queue holds; defaults to 1024.
+    pub fn with_buffer_capacity(mut self, capacity: usize) -> Self {
+        self.buffer_capacity = capacity;
+        self
+    }
+    /// What async mode does when the queue is full; defaults to Block.
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+    /// Defaults to EveryRecord.
+    pub fn with_flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.flush_policy = policy;
+        self
+    }
+    /// On startup, keep appending to the newest log file if it hasn't reached
+    /// either rolling threshold yet instead of always starting a new one.
+    pub fn with_reuse_latest(mut self, reuse_latest: bool) -> Self {
+        self.reuse_latest = reuse_latest;
+        self
+    }
+    /// Keeps `<prefix>latest.log` in the logs directory pointing at the active file.
+    pub fn with_latest_symlink(mut self, latest_symlink: bool) -> Self {
+        self.latest_symlink = latest_symlink;
+        self
+    }
+    /// Caps every call site at `per_second` records per second. How many a site
+    /// dropped is reported with the first record logged after its second is up,
+    /// or on flush and drop.
+    pub fn with_rate_limit(mut self, per_second: u32) -> Self {
+        self.rate_limit = Some(per_second);
+        self
+    }
+    /// Collapses consecutive identical records (same level, target, message and
+    /// fields) into a "last message repeated N times" line, written when a different
+    /// record arrives, on flush or drop, or once dedup_interval has passed. It always
+    /// goes into the file holding the repeats, ahead of any rotation.
+    pub fn with_dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+    /// How often a run of repeats is reported while it lasts; defaults to 30s.
+    pub fn with_dedup_interval(mut self, interval: Duration) -> Self {
+        self.dedup_interval = interval;
+        self
+    }
+    /// Adds the Debug form of each layer to error_chain records.
+    pub fn with_error_debug(mut self, error_debug: bool) -> Self {
+        self.error_debug = error_debug;
+        self
+    }
+    /// Defaults to None.
+    pub fn with_sync_mode(mut self, sync_mode: SyncMode) -> Self {
+        self.sync_mode = sync_mode;
+        self
+    }
+    /// Adds the thread name (or a per-thread number for unnamed threads) to every
+    /// record: after the level with the default text pattern, as thread_name and
+    /// thread_id in JSON and logfmt. Custom patterns can use %T instead.
+    pub fn with_thread_info(mut self, thread_info: bool) -> Self {
+        self.thread_info = thread_info;
+        self
+    }
+    /// Adds the host name and process id to every record: as a `[host:pid]` block
+    /// with the default text pattern, as host and pid in JSON and logfmt. Custom
+    /// patterns can use %H instead. The name comes from with_instance_name, then
+    /// the LOGTAR_HOSTNAME environment variable, then the OS.
+    pub fn with_host_info(mut self, host_info: bool) -> Self {
+        self.host_info = host_info;
+        self
+    }
+    /// The name host info shows instead of the host name.
+    pub fn with_instance_name(mut self, name: String) -> Self {
+        self.instance_name = Some(name);
+        self
+    }
+    /// Also decides where aligned rotation periods begin and end. Fixed offsets of
+    /// a day or more are rejected.
+    pub fn with_timezone(mut self, timezone: Timezone) -> Result<Self, ConfigError> {
+        self.timezone = timezone
+            .validate()
+            .map_err(|reason| ConfigError::out_of_range("timezone", reason))?;
+        Ok(self)
+    }
+    /// Defaults to SamePath.
+    pub fn with_reopen_mode(mut self, reopen_mode: ReopenMode) -> Self {
+        self.reopen_mode = reopen_mode;
+        self
+    }
+    /// Replaces every match in messages, error chains and string field values with
+    /// `[REDACTED]`, in all formats and on the console. Patterns use the syntax of
+    /// the regex crate.
+    pub fn with_redact_patterns(mut self, patterns: &[&str]) -> Result<Self, ConfigError> {
+        self.redact_patterns =
+            RedactPatterns::compile(patterns.iter().copied()).map_err(|(pattern, e)| {
+                ConfigError::InvalidRedactPattern {
+                    pattern,
+                    reason: e.to_string(),
+                }
+            })?;
+        Ok(self)
+    }
+    /// Field keys, compared case-insensitively, whose values are always redacted.
+    pub fn with_redact_keys(mut self, keys: Vec<String>) -> Self {
+        self.redact_keys = keys;
+        self
+    }
+    /// Longer messages are cut to this many bytes plus a "…[truncated N bytes]"
+    /// marker. Unlimited by default.
+    pub fn with_max_message_len(mut self, max_len: usize) -> Self {
+        self.max_message_len = Some(max_len);
+        self
+    }
+    /// The same for each string field value.
+    pub fn with_max_field_len(mut self, max_len: usize) -> Self {
+        self.max_field_len = Some(max_len);
+        self
+    }
+    /// Defaults to Raw.
+    pub fn with_multiline(mut self, multiline: Multiline) -> Self {
+        self.multiline = multiline;
+        self
+    }
+    /// Fields that get their own CSV column, in this order, after the built-in
+    /// ones. Records without one leave its column empty; other fields are left
+    /// out of CSV output.
+    pub fn with_csv_fields(mut self, fields: Vec<String>) -> Self {
+        self.csv_fields = fields;
+        self
+    }
+    /// Makes log files tamper-evident: every line ends with a SHA-256 chained to
+    /// the line before, starting from a genesis hash at the top of the file, and
+    /// a rotated file ends with a trailer naming the next one. Line breaks in
+    /// records are escaped. Check files with Logger::verify_file. Only applies
+    /// to file output, and always starts a new file instead of reusing one.
+    pub fn with_audit(mut self, audit: bool) -> Self {
+        self.audit = audit;
+        self
+    }
+    /// What to do on startup when another logger, in this process or another,
+    /// is writing with the same directory and prefix.
+    pub fn with_lock_conflict(mut self, lock_conflict: LockConflict) -> Self {
+        self.lock_conflict = lock_conflict;
+        self
+    }
+    /// Adds a destination next to the main output; can be called more than once.
+    pub fn add_sink(mut self, sink: Sink) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+    /// Replaces the system clock, e.g. with a ManualClock in tests.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = ClockHandle::new(clock);
+        self
+    }
 
-    pub fn from_json_file(file_path: &Path) -> Self {
-        let mut config_file = "".to_string();
-        File::open(file_path)
-            .expect("LogLevel reading config")
-            .read_to_string(&mut config_file)
-            .unwrap();
+    // Longest `::`-separated prefix wins, so "hyper" covers "hyper::client" but
+    // not "hyper_util".
+    fn level_for(&self, target: &str) -> Option<LogLevel> {
+        let mut prefix = target;
+        loop {
+            if let Some(level) = self.target_levels.get(prefix) {
+                return Some(*level);
+            }
+            prefix = &prefix[..prefix.rfind("::")?];
+        }
+    }
 
-        let js: Value = serde_json::from_str(&config_file).unwrap();
-        let keys = js.as_object().expect("Cannot convert JSON to Dictionary");
+    /// Unset variables keep their defaults; use the with_* builders afterwards to
+    /// override anything coming from the environment.
+    pub fn from_env() -> Result<Self, ConfigError> {
         let mut config = Self::new();
+        if let Some(value) = env_var("LOGTAR_LEVEL")? {
+            let level = value
+                .parse::<LogLevel>()
+                .map_err(|e| ConfigError::invalid_env("LOGTAR_LEVEL", &value, &e.to_string()))?;
+            config = config.with_level(level);
+        }
+        if let Some(value) = env_var("LOGTAR_FILE_PREFIX")? {
+            config = config.with_file_prefix(value);
+        }
+        if let Some(value) = env_var("LOGTAR_DIR")? {
+            config = config.with_directory(PathBuf::from(value));
+        }
+        if let Some(value) = env_var("LOGTAR_ROLL_SIZE")? {
+            let bytes = size::parse_size(&value)
+                .map_err(|reason| ConfigError::invalid_env("LOGTAR_ROLL_SIZE", &value, &reason))?;
+            config.rolling_config = config.rolling_config.with_size_bytes(bytes);
+        }
+        if let Some(value) = env_var("LOGTAR_ROLL_TIME")? {
+            let time = value
+                .parse::<u64>()
+                .map_err(|_| "Expected a number of seconds")
+                .and_then(RollingTimeOptions::from_u64)
+                .map_err(|reason| ConfigError::invalid_env("LOGTAR_ROLL_TIME", &value, reason))?;
+            config.rolling_config = config.rolling_config.with_time_threshold(time);
+        }
+
+        Ok(config)
+    }
+
+    /// Dispatches on the file extension.
+    pub fn from_file(file_path: &Path) -> Result<Self, ConfigError> {
+        match file_path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json_file(file_path),
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml_file(file_path),
+            _ => Err(ConfigError::UnsupportedFormat(file_path.to_path_buf())),
+        }
+    }
+
+    /// Unknown keys don't fail the load; they are collected in `warnings()`.
+    pub fn from_json_file(file_path: &Path) -> Result<Self, ConfigError> {
+        let config_file = read_config_file(file_path)?;
+        let js: Value = serde_json::from_str(&config_file).map_err(ConfigError::Json)?;
+        Self::from_value(&js)
+    }
+
+    /// Same keys as the JSON loader, e.g. `[rolling_config]` as a table.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_file(file_path: &Path) -> Result<Self, ConfigError> {
+        let config_file = read_config_file(file_path)?;
+        let js: Value = toml::from_str(&config_file).map_err(ConfigError::Toml)?;
+        Self::from_value(&js)
+    }
+
+    // The keys below keep their own parsing, which takes numeric levels and
+    // reports bad values by key. Everything else goes through Deserialize, so
+    // the files accept the same keys as serde.
+    fn from_value(js: &Value) -> Result<Self, ConfigError> {
+        let keys = js.as_object().ok_or_else(|| ConfigError::WrongType {
+            key: "<root>".to_string(),
+            expected: "an object",
+        })?;
+        const PARSED_KEYS: [&str; 5] = [
+            "level",
+            "rolling_config",
+            "file_prefix",
+            "directory",
+            "pattern",
+        ];
+        let known = struct_fields::<Self>();
+        let mut rest = serde_json::Map::new();
+        let mut warnings = Vec::new();
+        for (k, v) in keys {
+            if PARSED_KEYS.contains(&k.as_str()) {
+                continue;
+            }
+            if !known.contains(&k.as_str()) {
+                warnings.push(format!("Unknown config key {:?}", k));
+                continue;
+            }
+            // Checked one at a time so an error names the key.
+            let single = Value::Object([(k.clone(), v.clone())].into_iter().collect());
+            serde_json::from_value::<Self>(single).map_err(|e| ConfigError::out_of_range(k, e))?;
+            rest.insert(k.clone(), v.clone());
+        }
+        let mut config: Self =
+            serde_json::from_value(Value::Object(rest)).map_err(ConfigError::Json)?;
+        config.warnings = warnings;
+
         for (k, v) in keys {
             match k.as_str() {
                 "level" => {
-                    config = config.with_level(
-                        LogLevel::from_u64(v.as_u64().expect("expexted config level to be number"))
-                            .expect("Expected log level to be in range 0..=4"),
-                    )
+                    let level = match v {
+                        Value::Number(_) => LogLevel::try_from(json_u64(k, v)?),
+                        Value::String(name) => name.parse(),
+                        _ => {
+                            return Err(ConfigError::WrongType {
+                                key: k.clone(),
+                                expected: "a number or a string",
+                            })
+                        }
+                    }
+                    .map_err(|e| ConfigError::out_of_range(k, e))?;
+                    config = config.with_level(level)
                 }
 
                 "rolling_config" => {
-                    config = config.with_rolling_config(RollingConfig::from_json(v))
+                    let rolling_config = RollingConfig::parse_json(v, &mut config.warnings)?;
+                    config = config.with_rolling_config(rolling_config)
                 }
-                "file_prefix" => {
-                    config = config.with_file_prefix(
-                        v.as_str()
-                            .expect("Expected file prefix to be a string")
-                            .to_string(),
-                    )
-                }
-                _ => continue,
+                "file_prefix" => config = config.with_file_prefix(json_str(k, v)?.to_string()),
+                "directory" => config = config.with_directory(PathBuf::from(json_str(k, v)?)),
+                "pattern" => config = config.with_pattern(json_str(k, v)?)?,
+                _ => {}
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub(crate) fn rolling_config(&self) -> &RollingConfig {
+        &self.rolling_config
+    }
+
+    pub(crate) fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    pub(crate) fn file_prefix(&self) -> &str {
+        &self.file_prefix
+    }
+
+    pub(crate) fn latest_symlink(&self) -> bool {
+        self.latest_symlink
+    }
+
+    pub(crate) fn sync_mode(&self) -> SyncMode {
+        self.sync_mode
+    }
+
+    pub(crate) fn timezone(&self) -> Timezone {
+        self.timezone
+    }
+
+    pub(crate) fn audit(&self) -> bool {
+        self.audit
+    }
+
+    // The first line of every new output when writing CSV.
+    pub(crate) fn csv_header(&self) -> Option<String> {
+        (self.format == LogFormat::Csv).then(|| record::csv_header(&self.csv_fields))
+    }
+
+    /// Unknown keys the file loaders skipped.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+}
+
+fn read_config_file(file_path: &Path) -> Result<String, ConfigError> {
+    let mut config_file = "".to_string();
+    File::open(file_path)
+        .and_then(|mut file| file.read_to_string(&mut config_file))
+        .map_err(|source| ConfigError::Io {
+            path: file_path.to_path_buf(),
+            source,
+        })?;
+    Ok(config_file)
+}
+
+// The field names a derived Deserialize passes to deserialize_struct, caught by
+// a deserializer that fails as soon as it has them.
+fn struct_fields<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    struct Fields(&'static [&'static str]);
+
+    impl<'de> Deserializer<'de> for &mut Fields {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: de::Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: de::Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            self.0 = fields;
+            Err(de::Error::custom("fields collected"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+            byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map
+            enum identifier ignored_any
+        }
+    }
+
+    let mut fields = Fields(&[]);
+    let _ = T::deserialize(&mut fields);
+    fields.0
+}
+
+fn json_u64(key: &str, value: &Value) -> Result<u64, ConfigError> {
+    value.as_u64().ok_or_else(|| ConfigError::WrongType {
+        key: key.to_string(),
+        expected: "a positive number",
+    })
+}
+
+fn json_str<'a>(key: &str, value: &'a Value) -> Result<&'a str, ConfigError> {
+    value.as_str().ok_or_else(|| ConfigError::WrongType {
+        key: key.to_string(),
+        expected: "a string",
+    })
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// When buffered records are pushed to the file. Interval is checked as records
+/// are written, not by a timer, so an idle logger keeps its buffer until the next
+/// record, flush() or drop.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlushPolicy {
+    EveryRecord,
+    EveryRecords(usize),
+    Interval(Duration),
+}
+
+/// Whether log files are fsynced (File::sync_data) so records survive a power
+/// loss. OnFlush syncs on every flush and rotation; EveryRecord syncs after each
+/// record, which costs a disk round trip per record (typically milliseconds on
+/// spinning disks, tens to hundreds of microseconds on SSDs). Custom writers are
+/// never synced.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncMode {
+    None,
+    OnFlush,
+    EveryRecord,
+}
+
+/// How text records show messages with line breaks, which line-based parsers
+/// would otherwise take for several records. Escape writes them as `\n`, Indent
+/// starts each continuation line with a tab, and Raw leaves them alone. JSON and
+/// logfmt always escape them.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Multiline {
+    Raw,
+    Escape,
+    Indent,
+}
+
+/// What Logger::reopen does with the log file. SamePath suits logrotate, which
+/// renames the file and expects a fresh one under the old name.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReopenMode {
+    SamePath,
+    NewFile,
+}
+
+/// What async mode does when the queue between callers and the writer thread
+/// is full.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    Block,
+    DropOldest,
+}
+
+/// Log files are locked while they are written to. With NewFile a second logger
+/// sharing the directory and prefix writes its own file, suffixed with `.N` if
+/// the name is taken, and never reuses or deletes a locked one. Error makes
+/// Logger::try_new fail instead.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockConflict {
+    NewFile,
+    Error,
+}
+
+/// How records are laid out in the main output or a sink.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    Text,
+    Json,
+    Logfmt,
+    Gelf,
+    /// Comma-separated values with a header row; see LogConfig::with_csv_fields.
+    Csv,
+}
+
+/// How many fractional digits timestamps get.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampPrecision {
+    Secs,
+    Millis,
+    Micros,
+    Nanos,
+}
+
+impl TimestampPrecision {
+    pub(crate) fn seconds_format(self) -> SecondsFormat {
+        match self {
+            TimestampPrecision::Secs => SecondsFormat::Secs,
+            TimestampPrecision::Millis => SecondsFormat::Millis,
+            TimestampPrecision::Micros => SecondsFormat::Micros,
+            TimestampPrecision::Nanos => SecondsFormat::Nanos,
+        }
+    }
+}
+
+/// The zone timestamps are shown in, in records and file names alike. Times are
+/// still kept in UTC internally.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", try_from = "TimezoneValue")]
+pub enum Timezone {
+    Utc,
+    Local,
+    /// Seconds east of UTC, less than a day either way; LogConfig rejects others.
+    FixedOffset(i32),
+}
+
+// Timezone as written in configs, before the offset is checked.
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum TimezoneValue {
+    Utc,
+    Local,
+    FixedOffset(i32),
+}
+
+impl TryFrom<TimezoneValue> for Timezone {
+    type Error = String;
+
+    fn try_from(value: TimezoneValue) -> Result<Self, String> {
+        match value {
+            TimezoneValue::Utc => Ok(Timezone::Utc),
+            TimezoneValue::Local => Ok(Timezone::Local),
+            TimezoneValue::FixedOffset(seconds) => Timezone::FixedOffset(seconds).validate(),
+        }
+    }
+}
+
+impl Timezone {
+    fn validate(self) -> Result<Self, String> {
+        match self {
+            Timezone::FixedOffset(seconds) if FixedOffset::east_opt(seconds).is_none() => {
+                Err(format!(
+                    "offset {} must be less than a day (86400 seconds) either way",
+                    seconds
+                ))
             }
+            timezone => Ok(timezone),
+        }
+    }
+
+    // Offsets are checked when the config is built or loaded, so the UTC
+    // fallback is never reached through LogConfig.
+    fn fixed(seconds: i32) -> FixedOffset {
+        FixedOffset::east_opt(seconds).unwrap_or(Utc.fix())
+    }
+
+    pub(crate) fn to_local(self, time: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Timezone::Utc => time.fixed_offset(),
+            Timezone::Local => time.with_timezone(&Local).fixed_offset(),
+            Timezone::FixedOffset(seconds) => time.with_timezone(&Self::fixed(seconds)),
         }
+    }
 
-        config
+    // For wall-clock times read back from file names or computed as period
+    // starts. An ambiguous local time takes the earlier instant, and one skipped
+    // by a DST jump is read as UTC.
+    pub(crate) fn to_utc(self, time: NaiveDateTime) -> DateTime<Utc> {
+        let local = match self {
+            Timezone::Utc => return time.and_utc(),
+            Timezone::Local => Local
+                .from_local_datetime(&time)
+                .earliest()
+                .map(|t| t.fixed_offset()),
+            Timezone::FixedOffset(seconds) => {
+                Self::fixed(seconds).from_local_datetime(&time).earliest()
+            }
+        };
+        local.map_or_else(|| time.and_utc(), |t| t.to_utc())
     }
 }
 
-#[derive(Debug)]
+/// When the log file is rotated: after a size, a time period, or both. By
+/// default every 5 MB or every hour, keeping all old files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct RollingConfig {
     time_threshold: RollingTimeOptions,
-    size_threshold: RollingSizeOptions,
+    // In bytes. The RollingSizeOptions presets only cover common values.
+    #[serde(deserialize_with = "size::deserialize_size")]
+    size_threshold: u64,
+    max_files: Option<usize>,
+    time_alignment: TimeAlignment,
 }
 
 impl RollingConfig {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             time_threshold: RollingTimeOptions::Hourly,
-            size_threshold: RollingSizeOptions::FiveMB,
+            size_threshold: RollingSizeOptions::FiveMB as u64,
+            max_files: None,
+            time_alignment: TimeAlignment::Elapsed,
         }
     }
 
+    /// Rotates at least this often.
     pub fn with_time_threshold(mut self, time: RollingTimeOptions) -> Self {
         self.time_threshold = time;
         self
     }
 
+    /// Rotates before a record would take the file past this size.
     pub fn with_size_threshold(mut self, size: RollingSizeOptions) -> Self {
-        self.size_threshold = size;
+        self.size_threshold = size as u64;
         self
     }
 
-    pub fn from_json(json_value: &Value) -> Self {
+    /// with_size_threshold for sizes the presets don't cover.
+    pub fn with_size_bytes(mut self, bytes: u64) -> Self {
+        self.size_threshold = bytes;
+        self
+    }
+
+    /// After each rotation, deletes the oldest log files until this many are left.
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = Some(max_files);
+        self
+    }
+
+    /// Defaults to Elapsed.
+    pub fn with_time_alignment(mut self, alignment: TimeAlignment) -> Self {
+        self.time_alignment = alignment;
+        self
+    }
+
+    pub(crate) fn time_threshold(&self) -> RollingTimeOptions {
+        self.time_threshold
+    }
+
+    pub(crate) fn size_threshold(&self) -> u64 {
+        self.size_threshold
+    }
+
+    pub(crate) fn max_files(&self) -> Option<usize> {
+        self.max_files
+    }
+
+    pub(crate) fn time_alignment(&self) -> TimeAlignment {
+        self.time_alignment
+    }
+
+    /// Reads the `rolling_config` object of a config file.
+    pub fn from_json(json_value: &Value) -> Result<Self, ConfigError> {
+        Self::parse_json(json_value, &mut Vec::new())
+    }
+
+    fn parse_json(json_value: &Value, warnings: &mut Vec<String>) -> Result<Self, ConfigError> {
         let keys = json_value
             .as_object()
-            .expect("Cannot convert JSON to Dictionary");
+            .ok_or_else(|| ConfigError::WrongType {
+                key: "rolling_config".to_string(),
+                expected: "an object",
+            })?;
         let mut rolling_config = Self::new();
         for (k, v) in keys {
+            let key = format!("rolling_config.{}", k);
             match k.as_str() {
                 "size_threshold" => {
-                    rolling_config = rolling_config.with_size_threshold(
-                        RollingSizeOptions::from_u64(
-                            v.as_u64().expect("expexted size threshold to be number"),
-                        )
-                        .unwrap(),
-                    )
+                    let bytes = match v {
+                        Value::Number(_) => match json_u64(&key, v)? {
+                            0 => Err("size must be greater than zero".to_string()),
+                            bytes => Ok(bytes),
+                        },
+                        Value::String(size) => size::parse_size(size),
+                        _ => {
+                            return Err(ConfigError::WrongType {
+                                key,
+                                expected: "a number or a string",
+                            })
+                        }
+                    }
+                    .map_err(|e| ConfigError::out_of_range(&key, e))?;
+                    rolling_config = rolling_config.with_size_bytes(bytes)
                 }
                 "time_threshold" => {
                     rolling_config = rolling_config.with_time_threshold(
-                        RollingTimeOptions::from_u64(
-                            v.as_u64().expect("expexted size threshold to be number"),
-                        )
-                        .unwrap(),
+                        RollingTimeOptions::from_u64(json_u64(&key, v)?)
+                            .map_err(|e| ConfigError::out_of_range(&key, e))?,
                     )
                 }
-                _ => continue,
+                "max_files" => {
+                    rolling_config = rolling_config.with_max_files(json_u64(&key, v)? as usize)
+                }
+                "time_alignment" => {
+                    let alignment = match json_str(&key, v)? {
+                        "elapsed" => TimeAlignment::Elapsed,
+                        "aligned" => TimeAlignment::Aligned,
+                        other => {
+                            return Err(ConfigError::out_of_range(
+                                &key,
+                                format!("expected \"elapsed\" or \"aligned\", got {:?}", other),
+                            ))
+                        }
+                    };
+                    rolling_config = rolling_config.with_time_alignment(alignment)
+                }
+                _ => warnings.push(format!("Unknown config key {:?}", key)),
             }
         }
 
-        rolling_config
+        Ok(rolling_config)
+    }
+}
+
+impl Default for RollingConfig {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
+/// Common size thresholds, in bytes.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum RollingSizeOptions {
     OneKB = 1024,
@@ -284,6 +2358,19 @@ pub enum RollingSizeOptions {
     HundredMB = 100 * 1024 * 1024,
 }
 
+// Serialized as the number of bytes, like in config files.
+impl Serialize for RollingSizeOptions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(*self as u64)
+    }
+}
+
+impl<'de> Deserialize<'de> for RollingSizeOptions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::from_u64(u64::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
 impl RollingSizeOptions {
     pub fn from_u64(value: u64) -> Result<Self, &'static str> {
         match value {
@@ -305,6 +2392,7 @@ impl RollingSizeOptions {
     }
 }
 
+/// Time thresholds, as their length in seconds.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum RollingTimeOptions {
     FiveSecs = 5,
@@ -316,8 +2404,70 @@ pub enum RollingTimeOptions {
     Yearly = 12 * 30 * 24 * 60 * 60,
 }
 
+/// Elapsed rolls once the time threshold has passed since the file was opened.
+/// Aligned rolls on calendar boundaries in the configured timezone (Daily at
+/// midnight, Hourly at the top of the hour, Weekly on Monday) and names files
+/// after the period they cover, e.g. `<prefix>2024-05-01.log` for Daily.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeAlignment {
+    Elapsed,
+    Aligned,
+}
+
+// Serialized as the number of seconds, like in config files.
+impl Serialize for RollingTimeOptions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(*self as u64)
+    }
+}
+
+impl<'de> Deserialize<'de> for RollingTimeOptions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::from_u64(u64::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
 impl RollingTimeOptions {
-    fn from_u64(value: u64) -> Result<Self, &'static str> {
+    // Start of the calendar period containing `time`, on the wall clock of
+    // `timezone`, so a daily file starts at local midnight. Monthly and Yearly
+    // follow the calendar rather than their nominal 30-day months.
+    pub(crate) fn period_start(self, time: DateTime<Utc>, timezone: Timezone) -> DateTime<Utc> {
+        let local = timezone.to_local(time);
+        let date = local.date_naive();
+        let start = match self {
+            RollingTimeOptions::FiveSecs
+            | RollingTimeOptions::Minutely
+            | RollingTimeOptions::Hourly => {
+                let offset = i64::from(local.offset().local_minus_utc());
+                let seconds = time.timestamp() + offset;
+                let start = seconds - seconds.rem_euclid(self as i64) - offset;
+                return DateTime::from_timestamp(start, 0).unwrap_or(time);
+            }
+            RollingTimeOptions::Daily => date,
+            RollingTimeOptions::Weekly => {
+                date - chrono::Days::new(date.weekday().num_days_from_monday().into())
+            }
+            RollingTimeOptions::Monthly => date.with_day(1).unwrap_or(date),
+            RollingTimeOptions::Yearly => date.with_ordinal(1).unwrap_or(date),
+        };
+        timezone.to_utc(start.and_time(NaiveTime::MIN))
+    }
+
+    // How aligned files are named; only as precise as the period needs.
+    pub(crate) fn period_format(self) -> &'static str {
+        match self {
+            RollingTimeOptions::FiveSecs => "%Y-%m-%dT%H-%M-%S",
+            RollingTimeOptions::Minutely => "%Y-%m-%dT%H-%M",
+            RollingTimeOptions::Hourly => "%Y-%m-%dT%H",
+            RollingTimeOptions::Daily => "%Y-%m-%d",
+            RollingTimeOptions::Weekly => "%G-W%V",
+            RollingTimeOptions::Monthly => "%Y-%m",
+            RollingTimeOptions::Yearly => "%Y",
+        }
+    }
+
+    pub fn from_u64(value: u64) -> Result<Self, &'static str> {
         match value {
             x if x == RollingTimeOptions::FiveSecs as u64 => Ok(RollingTimeOptions::FiveSecs),
             x if x == RollingTimeOptions::Minutely as u64 => Ok(RollingTimeOptions::Minutely),
@@ -0,0 +1,103 @@
+// The level check happens before the message is formatted, so filtered records
+// cost nothing beyond the comparison.
+#[macro_export]
+macro_rules! log {
+    (target: $target:expr, $logger:expr, $level:expr, $($arg:tt)+) => {{
+        let logger = &$logger;
+        let level = $level;
+        let target = $target;
+        if logger.should_log(level, target) {
+            logger.log_checked(level, target, &::std::format!($($arg)+), &[]);
+        }
+    }};
+    ($logger:expr, $level:expr, $($arg:tt)+) => {
+        $crate::log!(target: ::std::module_path!(), $logger, $level, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! trace {
+    (target: $target:expr, $logger:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $logger, $crate::LogLevel::Trace, $($arg)+)
+    };
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($logger, $crate::LogLevel::Trace, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! debug {
+    (target: $target:expr, $logger:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $logger, $crate::LogLevel::Debug, $($arg)+)
+    };
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($logger, $crate::LogLevel::Debug, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! info {
+    (target: $target:expr, $logger:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $logger, $crate::LogLevel::Info, $($arg)+)
+    };
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($logger, $crate::LogLevel::Info, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! warn {
+    (target: $target:expr, $logger:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $logger, $crate::LogLevel::Warn, $($arg)+)
+    };
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($logger, $crate::LogLevel::Warn, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    (target: $target:expr, $logger:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $logger, $crate::LogLevel::Error, $($arg)+)
+    };
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($logger, $crate::LogLevel::Error, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! critical {
+    (target: $target:expr, $logger:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $logger, $crate::LogLevel::Critical, $($arg)+)
+    };
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($logger, $crate::LogLevel::Critical, $($arg)+)
+    };
+}
+
+// Rate-limited variants, e.g. `warn_limited!(logger, 10 / sec, "retrying {}", id)`.
+// The limit is per call site and overrides with_rate_limit.
+#[macro_export]
+macro_rules! log_limited {
+    (target: $target:expr, $logger:expr, $level:expr, $rate:literal / sec, $($arg:tt)+) => {{
+        let logger = &$logger;
+        let level = $level;
+        let target = $target;
+        if logger.should_log(level, target) {
+            logger.log_limited(level, target, $rate, &::std::format!($($arg)+), &[]);
+        }
+    }};
+    ($logger:expr, $level:expr, $rate:literal / sec, $($arg:tt)+) => {
+        $crate::log_limited!(target: ::std::module_path!(), $logger, $level, $rate / sec, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! warn_limited {
+    (target: $target:expr, $logger:expr, $rate:literal / sec, $($arg:tt)+) => {
+        $crate::log_limited!(target: $target, $logger, $crate::LogLevel::Warn, $rate / sec, $($arg)+)
+    };
+    ($logger:expr, $rate:literal / sec, $($arg:tt)+) => {
+        $crate::log_limited!($logger, $crate::LogLevel::Warn, $rate / sec, $($arg)+)
+    };
+}
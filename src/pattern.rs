@@ -0,0 +1,180 @@
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    logger::TimestampPrecision,
+    record::{Record, ThreadInfo},
+};
+
+// The layout records used before patterns existed.
+pub(crate) const DEFAULT_PATTERN: &str = "%t %l [%f:%L] %m";
+// Used instead of the default when thread or host info is on.
+const DEFAULT_THREAD_PATTERN: &str = "%t %l [%T] [%f:%L] %m";
+const DEFAULT_HOST_PATTERN: &str = "%t %l [%H] [%f:%L] %m";
+const DEFAULT_HOST_THREAD_PATTERN: &str = "%t %l [%H] [%T] [%f:%L] %m";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Literal(String),
+    Timestamp,
+    Level,
+    File,
+    Line,
+    Message,
+    Thread,
+    Host,
+}
+
+// A text layout parsed once when the config is built, so logging only walks the
+// pieces instead of re-scanning the template. Serialized as the template string.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub(crate) struct Pattern {
+    source: String,
+    pieces: Vec<Piece>,
+}
+
+impl Pattern {
+    pub(crate) fn parse(pattern: &str) -> Result<Self, String> {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+            let piece = match chars.next() {
+                Some('%') => {
+                    literal.push('%');
+                    continue;
+                }
+                Some('t') => Piece::Timestamp,
+                Some('l') => Piece::Level,
+                Some('f') => Piece::File,
+                Some('L') => Piece::Line,
+                Some('m') => Piece::Message,
+                Some('T') => Piece::Thread,
+                Some('H') => Piece::Host,
+                Some(other) => return Err(format!("unknown placeholder %{}", other)),
+                None => return Err("pattern ends with a lone %".to_string()),
+            };
+            if !literal.is_empty() {
+                pieces.push(Piece::Literal(std::mem::take(&mut literal)));
+            }
+            pieces.push(piece);
+        }
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+        Ok(Self {
+            source: pattern.to_string(),
+            pieces,
+        })
+    }
+
+    pub(crate) fn render(&self, record: &Record, precision: TimestampPrecision, line: &mut String) {
+        for piece in &self.pieces {
+            match piece {
+                Piece::Literal(text) => line.push_str(text),
+                Piece::Timestamp => line.push_str(&record.timestamp_string(precision)),
+                Piece::Level => line.push_str(&record.level.to_string()),
+                Piece::File => line.push_str(record.file),
+                Piece::Line => line.push_str(&record.line.to_string()),
+                Piece::Message => line.push_str(record.message),
+                // Records are formatted on the calling thread, so this is the
+                // caller's thread even in async mode.
+                Piece::Thread => match record.thread {
+                    Some(thread) => line.push_str(&thread.to_string()),
+                    None => line.push_str(&ThreadInfo::of(&thread::current()).to_string()),
+                },
+                Piece::Host => {
+                    if let Some(host) = record.host {
+                        line.push_str(&host.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    // The pattern text records are laid out with: the default one gains the
+    // host and thread when those are on, custom ones are left as written.
+    pub(crate) fn for_text(&self, thread_info: bool, host_info: bool) -> Self {
+        if self.source != DEFAULT_PATTERN {
+            return self.clone();
+        }
+        let pattern = match (host_info, thread_info) {
+            (false, false) => return self.clone(),
+            (false, true) => DEFAULT_THREAD_PATTERN,
+            (true, false) => DEFAULT_HOST_PATTERN,
+            (true, true) => DEFAULT_HOST_THREAD_PATTERN,
+        };
+        Self::parse(pattern).expect("default pattern is valid")
+    }
+}
+
+impl TryFrom<String> for Pattern {
+    type Error = String;
+
+    fn try_from(pattern: String) -> Result<Self, String> {
+        Self::parse(&pattern)
+    }
+}
+
+impl From<Pattern> for String {
+    fn from(pattern: Pattern) -> Self {
+        pattern.source
+    }
+}
+
+impl Default for Pattern {
+    fn default() -> Self {
+        Self::parse(DEFAULT_PATTERN).expect("default pattern is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literals_between_placeholders_are_merged() {
+        let pattern = Pattern::parse("[%l] 100%% %m!").unwrap();
+        assert_eq!(
+            pattern.pieces,
+            [
+                Piece::Literal("[".to_string()),
+                Piece::Level,
+                Piece::Literal("] 100% ".to_string()),
+                Piece::Message,
+                Piece::Literal("!".to_string()),
+            ]
+        );
+        assert_eq!(String::from(pattern), "[%l] 100%% %m!");
+    }
+
+    #[test]
+    fn bad_placeholders_are_rejected() {
+        assert_eq!(
+            Pattern::parse("%l %x").unwrap_err(),
+            "unknown placeholder %x"
+        );
+        assert_eq!(
+            Pattern::parse("%m %").unwrap_err(),
+            "pattern ends with a lone %"
+        );
+    }
+
+    #[test]
+    fn only_the_default_pattern_gains_thread_and_host() {
+        let default = Pattern::default();
+        assert_eq!(default.for_text(false, false), default);
+        assert_eq!(
+            default.for_text(true, true).source,
+            DEFAULT_HOST_THREAD_PATTERN
+        );
+        let custom = Pattern::parse("%l %m").unwrap();
+        assert_eq!(custom.for_text(true, true), custom);
+    }
+}
@@ -0,0 +1,500 @@
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+    thread::{self, Thread},
+};
+
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+
+use crate::{
+    host::HostInfo,
+    logger::{LogFormat, LogLevel, Multiline, TimestampPrecision, Timezone},
+    pattern::Pattern,
+    syslog,
+};
+
+/// The value of a structured field. Most primitives convert with `.into()`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogValue {
+    Str(String),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl fmt::Display for LogValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogValue::Str(v) => write!(f, "{}", v),
+            LogValue::Int(v) => write!(f, "{}", v),
+            LogValue::UInt(v) => write!(f, "{}", v),
+            LogValue::Float(v) => write!(f, "{}", v),
+            LogValue::Bool(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl From<&LogValue> for Value {
+    fn from(value: &LogValue) -> Self {
+        match value {
+            LogValue::Str(v) => Value::from(v.as_str()),
+            LogValue::Int(v) => Value::from(*v),
+            LogValue::UInt(v) => Value::from(*v),
+            LogValue::Float(v) => Value::from(*v),
+            LogValue::Bool(v) => Value::from(*v),
+        }
+    }
+}
+
+impl From<&str> for LogValue {
+    fn from(value: &str) -> Self {
+        LogValue::Str(value.to_string())
+    }
+}
+
+impl From<String> for LogValue {
+    fn from(value: String) -> Self {
+        LogValue::Str(value)
+    }
+}
+
+impl From<i32> for LogValue {
+    fn from(value: i32) -> Self {
+        LogValue::Int(value.into())
+    }
+}
+
+impl From<i64> for LogValue {
+    fn from(value: i64) -> Self {
+        LogValue::Int(value)
+    }
+}
+
+impl From<u32> for LogValue {
+    fn from(value: u32) -> Self {
+        LogValue::UInt(value.into())
+    }
+}
+
+impl From<u64> for LogValue {
+    fn from(value: u64) -> Self {
+        LogValue::UInt(value)
+    }
+}
+
+impl From<usize> for LogValue {
+    fn from(value: usize) -> Self {
+        LogValue::UInt(value as u64)
+    }
+}
+
+impl From<f64> for LogValue {
+    fn from(value: f64) -> Self {
+        LogValue::Float(value)
+    }
+}
+
+impl From<bool> for LogValue {
+    fn from(value: bool) -> Self {
+        LogValue::Bool(value)
+    }
+}
+
+// Numbered in the order threads first log, starting at 1. std's ThreadId has no
+// stable numeric form.
+fn thread_number() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    thread_local! {
+        static NUMBER: Cell<u64> = const { Cell::new(0) };
+    }
+    NUMBER.with(|number| {
+        if number.get() == 0 {
+            number.set(NEXT.fetch_add(1, Ordering::Relaxed));
+        }
+        number.get()
+    })
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct ThreadInfo<'a> {
+    pub(crate) id: u64,
+    pub(crate) name: Option<&'a str>,
+}
+
+impl<'a> ThreadInfo<'a> {
+    pub(crate) fn of(thread: &'a Thread) -> Self {
+        Self {
+            id: thread_number(),
+            name: thread.name(),
+        }
+    }
+}
+
+// The name, or the id for unnamed threads.
+impl fmt::Display for ThreadInfo<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", self.id),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct Record<'a> {
+    pub(crate) timestamp: DateTime<Utc>,
+    pub(crate) level: LogLevel,
+    pub(crate) file: &'a str,
+    pub(crate) line: u32,
+    pub(crate) message: &'a str,
+    pub(crate) fields: &'a [(&'a str, LogValue)],
+    // The macros' and the log facade's target; plain methods have none.
+    pub(crate) target: Option<&'a str>,
+    // Set by Logger::error_chain; the message already contains the joined chain.
+    pub(crate) error_chain: &'a [String],
+    // Only set when thread info is turned on.
+    pub(crate) thread: Option<ThreadInfo<'a>>,
+    // Always set for text so %H renders; JSON and logfmt only get it when host
+    // info is turned on.
+    pub(crate) host: Option<&'a HostInfo>,
+    // Only affect how the timestamp and text lines are rendered.
+    pub(crate) timezone: Timezone,
+    pub(crate) multiline: Multiline,
+    // The fields given their own CSV columns.
+    pub(crate) csv_fields: &'a [String],
+}
+
+impl<'a> Record<'a> {
+    pub(crate) fn new(
+        timestamp: DateTime<Utc>,
+        level: LogLevel,
+        message: &'a str,
+        file: &'a str,
+        line: u32,
+        fields: &'a [(&'a str, LogValue)],
+    ) -> Self {
+        Self {
+            timestamp,
+            level,
+            file,
+            line,
+            message,
+            fields,
+            target: None,
+            error_chain: &[],
+            thread: None,
+            host: None,
+            timezone: Timezone::Utc,
+            multiline: Multiline::Raw,
+            csv_fields: &[],
+        }
+    }
+
+    pub(crate) fn timestamp_string(&self, precision: TimestampPrecision) -> String {
+        self.timezone
+            .to_local(self.timestamp)
+            .to_rfc3339_opts(precision.seconds_format(), true)
+    }
+
+    pub(crate) fn format(
+        &self,
+        format: LogFormat,
+        precision: TimestampPrecision,
+        pattern: &Pattern,
+    ) -> String {
+        match format {
+            LogFormat::Text => self.format_text(precision, pattern),
+            LogFormat::Json => self.format_json(precision),
+            LogFormat::Logfmt => self.format_logfmt(precision),
+            LogFormat::Gelf => self.format_gelf(),
+            LogFormat::Csv => self.format_csv(precision),
+        }
+    }
+
+    // Fields always follow the pattern as key=value pairs. They are quoted and
+    // escaped anyway, so only the pattern part can contain line breaks.
+    pub(crate) fn format_text(&self, precision: TimestampPrecision, pattern: &Pattern) -> String {
+        let mut line = String::new();
+        pattern.render(self, precision, &mut line);
+        match self.multiline {
+            Multiline::Raw => {}
+            Multiline::Escape if line.contains(['\n', '\r']) => {
+                line = line.replace('\r', "\\r").replace('\n', "\\n");
+            }
+            Multiline::Indent if line.contains('\n') => line = line.replace('\n', "\n\t"),
+            Multiline::Escape | Multiline::Indent => {}
+        }
+        for (key, value) in self.fields {
+            line.push(' ');
+            line.push_str(key);
+            line.push('=');
+            push_quoted(&mut line, &value.to_string());
+        }
+        line.push('\n');
+        line
+    }
+
+    // serde_json takes care of escaping, so messages with quotes or newlines
+    // still produce exactly one line.
+    pub(crate) fn format_json(&self, precision: TimestampPrecision) -> String {
+        let mut object = Map::new();
+        object.insert(
+            "timestamp".to_string(),
+            Value::from(self.timestamp_string(precision)),
+        );
+        object.insert("level".to_string(), Value::from(self.level.to_string()));
+        object.insert("message".to_string(), Value::from(self.message));
+        object.insert("file".to_string(), Value::from(self.file));
+        object.insert("line".to_string(), Value::from(self.line));
+        if let Some(thread) = self.thread {
+            object.insert("thread_name".to_string(), Value::from(thread.name));
+            object.insert("thread_id".to_string(), Value::from(thread.id));
+        }
+        if let Some(host) = self.host {
+            object.insert("host".to_string(), Value::from(host.name.as_str()));
+            object.insert("pid".to_string(), Value::from(host.pid));
+        }
+        if !self.fields.is_empty() {
+            let fields = self
+                .fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), Value::from(value)))
+                .collect();
+            object.insert("fields".to_string(), Value::Object(fields));
+        }
+        if !self.error_chain.is_empty() {
+            let chain = self
+                .error_chain
+                .iter()
+                .map(|layer| Value::from(layer.as_str()));
+            object.insert("error_chain".to_string(), Value::Array(chain.collect()));
+        }
+
+        let mut line = Value::Object(object).to_string();
+        line.push('\n');
+        line
+    }
+
+    // Fields named like one of the built-in keys get a "field_" prefix so they
+    // can't shadow them.
+    pub(crate) fn format_logfmt(&self, precision: TimestampPrecision) -> String {
+        let mut line = format!(
+            "ts={} level={} msg=",
+            self.timestamp_string(precision),
+            self.level.to_string().to_lowercase()
+        );
+        push_quoted(&mut line, self.message);
+        line.push_str(" file=");
+        push_quoted(&mut line, self.file);
+        line.push_str(" line=");
+        line.push_str(&self.line.to_string());
+        if let Some(thread) = self.thread {
+            if let Some(name) = thread.name {
+                line.push_str(" thread_name=");
+                push_quoted(&mut line, name);
+            }
+            line.push_str(" thread_id=");
+            line.push_str(&thread.id.to_string());
+        }
+        if let Some(host) = self.host {
+            line.push_str(" host=");
+            push_quoted(&mut line, &host.name);
+            line.push_str(" pid=");
+            line.push_str(&host.pid.to_string());
+        }
+        for (key, value) in self.fields {
+            line.push(' ');
+            if LOGFMT_RESERVED_KEYS.contains(key) {
+                line.push_str("field_");
+            }
+            line.push_str(key);
+            line.push('=');
+            push_quoted(&mut line, &value.to_string());
+        }
+        line.push('\n');
+        line
+    }
+
+    // GELF 1.1: the timestamp is always epoch seconds with millis, the level
+    // uses syslog numbering, and everything beyond the standard keys goes in
+    // underscore-prefixed additional fields. Fields named like one of the
+    // built-in extras, or "id" since Graylog reserves `_id`, get a "field_"
+    // prefix like in logfmt.
+    pub(crate) fn format_gelf(&self) -> String {
+        let mut object = Map::new();
+        object.insert("version".to_string(), Value::from("1.1"));
+        let host = self.host.map_or("unknown", |host| host.name.as_str());
+        object.insert("host".to_string(), Value::from(host));
+        let short_message = self.message.lines().next().unwrap_or_default();
+        object.insert("short_message".to_string(), Value::from(short_message));
+        if self.message.contains('\n') {
+            object.insert("full_message".to_string(), Value::from(self.message));
+        } else if self.error_chain.len() > 1 {
+            let full_message = self.error_chain.join("\n");
+            object.insert("full_message".to_string(), Value::from(full_message));
+        }
+        let timestamp = self.timestamp.timestamp_millis() as f64 / 1000.0;
+        object.insert("timestamp".to_string(), Value::from(timestamp));
+        object.insert(
+            "level".to_string(),
+            Value::from(syslog::severity(self.level)),
+        );
+        object.insert(
+            "_log_level".to_string(),
+            Value::from(self.level.to_string()),
+        );
+        object.insert("_file".to_string(), Value::from(self.file));
+        object.insert("_line".to_string(), Value::from(self.line));
+        if let Some(thread) = self.thread {
+            if let Some(name) = thread.name {
+                object.insert("_thread_name".to_string(), Value::from(name));
+            }
+            object.insert("_thread_id".to_string(), Value::from(thread.id));
+        }
+        if let Some(host) = self.host {
+            object.insert("_pid".to_string(), Value::from(host.pid));
+        }
+        for (key, value) in self.fields {
+            let mut name = String::from("_");
+            if GELF_RESERVED_KEYS.contains(key) {
+                name.push_str("field_");
+            }
+            // Graylog only accepts word characters, dots and dashes in names.
+            name.extend(key.chars().map(|c| match c {
+                c if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-') => c,
+                _ => '_',
+            }));
+            // Additional field values can only be strings or numbers.
+            let value = match value {
+                LogValue::Bool(v) => Value::from(v.to_string()),
+                value => Value::from(value),
+            };
+            object.insert(name, value);
+        }
+
+        let mut line = Value::Object(object).to_string();
+        line.push('\n');
+        line
+    }
+
+    // One row in the columns of csv_header. The thread is always filled in, as
+    // with %T. Rows end in a plain newline like every other format; quoted
+    // values may still contain line breaks.
+    pub(crate) fn format_csv(&self, precision: TimestampPrecision) -> String {
+        let thread = match self.thread {
+            Some(thread) => thread.to_string(),
+            None => ThreadInfo::of(&thread::current()).to_string(),
+        };
+        let mut line = String::new();
+        push_csv(&mut line, &self.timestamp_string(precision));
+        line.push(',');
+        push_csv(&mut line, &self.level.to_string());
+        line.push(',');
+        push_csv(&mut line, self.file);
+        line.push(',');
+        push_csv(&mut line, &self.line.to_string());
+        line.push(',');
+        push_csv(&mut line, &thread);
+        line.push(',');
+        push_csv(&mut line, self.message);
+        for name in self.csv_fields {
+            line.push(',');
+            // Later fields win, as with bound fields.
+            let value = self.fields.iter().rev().find(|(key, _)| key == name);
+            if let Some((_, value)) = value {
+                push_csv(&mut line, &value.to_string());
+            }
+        }
+        line.push('\n');
+        line
+    }
+}
+
+pub(crate) fn csv_header(fields: &[String]) -> String {
+    let mut line = "timestamp,level,file,line,thread,message".to_string();
+    for name in fields {
+        line.push(',');
+        push_csv(&mut line, name);
+    }
+    line.push('\n');
+    line
+}
+
+const GELF_RESERVED_KEYS: [&str; 7] = [
+    "id",
+    "log_level",
+    "file",
+    "line",
+    "thread_name",
+    "thread_id",
+    "pid",
+];
+
+const LOGFMT_RESERVED_KEYS: [&str; 9] = [
+    "ts",
+    "level",
+    "msg",
+    "file",
+    "line",
+    "thread_name",
+    "thread_id",
+    "host",
+    "pid",
+];
+
+// Cuts `text` to at most `max_len` bytes, backing off to a char boundary so the
+// result stays valid UTF-8, and appends a marker. Returns how many bytes were cut.
+pub(crate) fn truncate(text: &mut Cow<'_, str>, max_len: usize) -> usize {
+    if text.len() <= max_len {
+        return 0;
+    }
+    let mut cut = max_len;
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    let removed = text.len() - cut;
+    *text = Cow::Owned(format!("{}…[truncated {} bytes]", &text[..cut], removed));
+    removed
+}
+
+// RFC 4180 quoting: values with a comma, quote or line break are wrapped in
+// quotes, with quotes inside doubled.
+fn push_csv(line: &mut String, value: &str) {
+    if !value.contains([',', '"', '\n', '\r']) {
+        line.push_str(value);
+        return;
+    }
+    line.push('"');
+    line.push_str(&value.replace('"', "\"\""));
+    line.push('"');
+}
+
+// Values are quoted only when they would otherwise be ambiguous to split on
+// whitespace or `=`.
+fn push_quoted(line: &mut String, value: &str) {
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || c == '"' || c == '=' || c == '\\');
+    if !needs_quotes {
+        line.push_str(value);
+        return;
+    }
+
+    line.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => line.push_str("\\\""),
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            '\t' => line.push_str("\\t"),
+            c => line.push(c),
+        }
+    }
+    line.push('"');
+}
@@ -0,0 +1,110 @@
+use std::borrow::Cow;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::record::LogValue;
+
+pub(crate) const REDACTED: &str = "[REDACTED]";
+
+// Serialized as the pattern strings, so a bad pattern in a config file fails
+// when the config is loaded rather than when something is logged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(try_from = "Vec<String>", into = "Vec<String>")]
+pub(crate) struct RedactPatterns(Vec<Regex>);
+
+impl RedactPatterns {
+    // On failure, returns the pattern that didn't compile and why.
+    pub(crate) fn compile<'a>(
+        patterns: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Self, (String, regex::Error)> {
+        patterns
+            .into_iter()
+            .map(|pattern| Regex::new(pattern).map_err(|e| (pattern.to_string(), e)))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // Matches of all patterns are collected against the original text first, so
+    // a replacement can't create or hide another match. Overlapping or touching
+    // matches collapse into a single marker.
+    pub(crate) fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut matches: Vec<(usize, usize)> = self
+            .0
+            .iter()
+            .flat_map(|pattern| pattern.find_iter(text))
+            .filter(|m| !m.is_empty())
+            .map(|m| (m.start(), m.end()))
+            .collect();
+        if matches.is_empty() {
+            return Cow::Borrowed(text);
+        }
+        matches.sort_unstable();
+
+        let mut redacted = String::with_capacity(text.len());
+        let mut copied = 0;
+        let mut current = matches[0];
+        for &(start, end) in &matches[1..] {
+            if start <= current.1 {
+                current.1 = current.1.max(end);
+                continue;
+            }
+            redacted.push_str(&text[copied..current.0]);
+            redacted.push_str(REDACTED);
+            copied = current.1;
+            current = (start, end);
+        }
+        redacted.push_str(&text[copied..current.0]);
+        redacted.push_str(REDACTED);
+        redacted.push_str(&text[current.1..]);
+        Cow::Owned(redacted)
+    }
+
+    // Values under a sensitive key are dropped entirely; other string values
+    // still go through the patterns.
+    pub(crate) fn redact_field(&self, keys: &[String], key: &str, value: &LogValue) -> LogValue {
+        if keys.iter().any(|k| k.eq_ignore_ascii_case(key)) {
+            return LogValue::Str(REDACTED.to_string());
+        }
+        match value {
+            LogValue::Str(text) => LogValue::Str(self.redact(text).into_owned()),
+            other => other.clone(),
+        }
+    }
+}
+
+// Regex has no PartialEq; patterns are equal if they were built from the same
+// strings.
+impl PartialEq for RedactPatterns {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self
+                .0
+                .iter()
+                .zip(&other.0)
+                .all(|(a, b)| a.as_str() == b.as_str())
+    }
+}
+
+impl TryFrom<Vec<String>> for RedactPatterns {
+    type Error = String;
+
+    fn try_from(patterns: Vec<String>) -> Result<Self, String> {
+        Self::compile(patterns.iter().map(String::as_str))
+            .map_err(|(pattern, e)| format!("invalid redact pattern {:?}: {}", pattern, e))
+    }
+}
+
+impl From<RedactPatterns> for Vec<String> {
+    fn from(patterns: RedactPatterns) -> Self {
+        patterns
+            .0
+            .iter()
+            .map(|pattern| pattern.as_str().to_string())
+            .collect()
+    }
+}
@@ -0,0 +1,36 @@
+use std::{
+    io::{self, Read, Write},
+    os::unix::net::UnixStream,
+    sync::Weak,
+    thread,
+};
+
+use crate::logger::LoggerInner;
+
+// A signal handler may only do async-signal-safe work, so it just wakes a
+// thread through a socket pair and the thread does the reopening.
+pub(crate) fn on_sighup(logger: Weak<LoggerInner>) -> io::Result<()> {
+    let (mut receiver, sender) = UnixStream::pair()?;
+    sender.set_nonblocking(true)?;
+    // SAFETY: the action only writes one byte to a socket, which is
+    // async-signal-safe. A full socket means a wakeup is already pending.
+    unsafe {
+        signal_hook_registry::register(libc::SIGHUP, move || {
+            let _ = (&sender).write(&[1]);
+        })?;
+    }
+    thread::Builder::new()
+        .name("logtar-sighup".to_string())
+        .spawn(move || {
+            let mut buf = [0u8; 64];
+            while matches!(receiver.read(&mut buf), Ok(n) if n > 0) {
+                let Some(logger) = logger.upgrade() else {
+                    break;
+                };
+                if let Err(e) = logger.reopen() {
+                    eprintln!("Could not reopen log file: {}", e);
+                }
+            }
+        })?;
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use std::{
+    fmt,
+    io::{self, Write},
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{
+    console::Console,
+    http::{HttpSinkConfig, HttpWriter},
+    logger::{LogFormat, LogLevel},
+    syslog::{SyslogConfig, SyslogWriter},
+    tcp::TcpWriter,
+};
+
+#[cfg(all(target_os = "linux", feature = "journald"))]
+use crate::journald::{self, JournaldWriter};
+
+/// An extra destination next to the logger's main output (its file or writer),
+/// with its own level and format. Records still have to pass the logger's level
+/// first. Rotation only ever applies to the main output. Clones share the writer.
+#[derive(Clone)]
+pub struct Sink {
+    writer: Arc<Mutex<Target>>,
+    min_level: LogLevel,
+    layout: Layout,
+}
+
+enum Target {
+    Stream(Console),
+    Syslog(SyslogWriter),
+    Tcp(TcpWriter),
+    Http(HttpWriter),
+    #[cfg(all(target_os = "linux", feature = "journald"))]
+    Journald(JournaldWriter),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Layout {
+    // The logger's format.
+    Main,
+    Format(LogFormat),
+    Syslog(SyslogConfig),
+    #[cfg(all(target_os = "linux", feature = "journald"))]
+    Journald,
+}
+
+impl Sink {
+    /// Writes records to any writer, uncolored.
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self::with_target(Target::Stream(Console::new(Box::new(writer), false)))
+    }
+
+    /// Colored by level on a terminal unless NO_COLOR is set.
+    pub fn stderr() -> Self {
+        Self::with_target(Target::Stream(Console::stderr(true)))
+    }
+
+    /// Sends each record as an RFC 5424 datagram. Send failures count as write
+    /// errors and never reach the caller.
+    pub fn syslog(config: SyslogConfig) -> Self {
+        let writer = SyslogWriter::new(config.transport().clone());
+        Self {
+            layout: Layout::Syslog(config),
+            ..Self::with_target(Target::Syslog(writer))
+        }
+    }
+
+    /// Newline-delimited records over a persistent connection, usually with
+    /// with_format(LogFormat::Json). Up to `buffer_capacity` records are kept
+    /// while the endpoint is unreachable; reconnects back off exponentially.
+    pub fn tcp(addr: SocketAddr, buffer_capacity: usize) -> Self {
+        Self::with_target(Target::Tcp(TcpWriter::spawn(addr, buffer_capacity)))
+    }
+
+    /// Posts records in batches as a JSON array, so its format has to be Json or
+    /// Gelf; it defaults to Json, and with_format keeps it there for any other
+    /// format. Dropping the last handle waits for a final send of what is left.
+    pub fn http(config: HttpSinkConfig) -> Self {
+        Self::with_target(Target::Http(HttpWriter::spawn(config))).with_format(LogFormat::Json)
+    }
+
+    /// Sends structured entries over journald's native protocol, with PRIORITY
+    /// from the level, CODE_FILE and CODE_LINE from the call site and fields as
+    /// uppercased journal fields. Entries too big for a datagram go through a
+    /// sealed memfd. Without a journal socket it warns once and drops records.
+    #[cfg(all(target_os = "linux", feature = "journald"))]
+    pub fn journald() -> Self {
+        Self::journald_at(journald::SOCKET_PATH)
+    }
+
+    /// journald() with another socket, e.g. a container's journal.
+    #[cfg(all(target_os = "linux", feature = "journald"))]
+    pub fn journald_at(path: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            layout: Layout::Journald,
+            ..Self::with_target(Target::Journald(JournaldWriter::new(path.into())))
+        }
+    }
+
+    fn with_target(target: Target) -> Self {
+        Self {
+            writer: Arc::new(Mutex::new(target)),
+            min_level: LogLevel::Trace,
+            layout: Layout::Main,
+        }
+    }
+
+    /// Skips records below `level` on this sink only.
+    pub fn with_min_level(mut self, level: LogLevel) -> Self {
+        self.min_level = level;
+        self
+    }
+
+    /// For a syslog or journald sink this replaces their own layout. An HTTP sink
+    /// ignores formats other than Json and Gelf, which wouldn't make a JSON array.
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        let json = matches!(format, LogFormat::Json | LogFormat::Gelf);
+        if json || !matches!(*self.target(), Target::Http(_)) {
+            self.layout = Layout::Format(format);
+        }
+        self
+    }
+
+    pub(crate) fn accepts(&self, level: LogLevel) -> bool {
+        level >= self.min_level
+    }
+
+    pub(crate) fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    // The format its records are written in, given the logger's, unless it
+    // lays them out for a protocol of its own.
+    pub(crate) fn format(&self, main: LogFormat) -> Option<LogFormat> {
+        match self.layout {
+            Layout::Main => Some(main),
+            Layout::Format(format) => Some(format),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn write_record(&self, level: LogLevel, record: &str) -> io::Result<()> {
+        match &mut *self.target() {
+            Target::Stream(console) => console.write_record(level, record),
+            Target::Syslog(writer) => writer.send(record),
+            Target::Tcp(writer) => {
+                writer.push(record);
+                Ok(())
+            }
+            Target::Http(writer) => {
+                writer.push(record);
+                Ok(())
+            }
+            #[cfg(all(target_os = "linux", feature = "journald"))]
+            Target::Journald(writer) => match self.layout {
+                Layout::Journald => writer.send(record),
+                _ => writer.send(&journald::message_entry(level, record)),
+            },
+        }
+    }
+
+    pub(crate) fn flush(&self) -> io::Result<()> {
+        match &mut *self.target() {
+            Target::Stream(console) => console.flush(),
+            // Flushing a network sink would mean waiting on the network.
+            Target::Syslog(_) | Target::Tcp(_) | Target::Http(_) => Ok(()),
+            #[cfg(all(target_os = "linux", feature = "journald"))]
+            Target::Journald(_) => Ok(()),
+        }
+    }
+
+    // Records a TCP or HTTP sink discarded because its buffer was full or its
+    // endpoint rejected them.
+    pub(crate) fn dropped(&self) -> u64 {
+        match &*self.target() {
+            Target::Tcp(writer) => writer.dropped(),
+            Target::Http(writer) => writer.dropped(),
+            Target::Stream(_) | Target::Syslog(_) => 0,
+            #[cfg(all(target_os = "linux", feature = "journald"))]
+            Target::Journald(_) => 0,
+        }
+    }
+
+    fn target(&self) -> MutexGuard<'_, Target> {
+        self.writer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// Two sinks are only equal if they share a writer.
+impl PartialEq for Sink {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.writer, &other.writer)
+            && self.min_level == other.min_level
+            && self.layout == other.layout
+    }
+}
+
+impl fmt::Debug for Sink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sink")
+            .field("min_level", &self.min_level)
+            .field("layout", &self.layout)
+            .finish_non_exhaustive()
+    }
+}
+
+// A record laid out for the main output and for each sink that wants it, in
+// the order the sinks were added.
+pub(crate) struct Lines {
+    pub(crate) main: String,
+    pub(crate) sinks: Vec<Option<String>>,
+    // Whether writing the main line may rotate the file first. Dedup summaries
+    // don't, so they land in the file holding the records they count.
+    pub(crate) rotates: bool,
+}
@@ -0,0 +1,138 @@
+use std::fmt;
+
+use serde::{de, Deserializer};
+
+// Parses sizes like "250MB", "1.5GiB", "512k" or a plain number of bytes. Units
+// are case-insensitive and binary, so "MB" and "MiB" both mean 1024 * 1024, the
+// same as the RollingSizeOptions variants.
+pub(crate) fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+        _ => return Err(format!("unknown size unit {:?}", unit.trim())),
+    };
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("expected a size like \"250MB\", got {:?}", value))?;
+    let bytes = (number * multiplier as f64).round();
+    if bytes < 1.0 {
+        return Err("size must be greater than zero".to_string());
+    }
+    if bytes >= u64::MAX as f64 {
+        return Err(format!("size {:?} is too large", value));
+    }
+    Ok(bytes as u64)
+}
+
+// Accepts either a number of bytes or a string for parse_size.
+pub(crate) fn deserialize_size<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u64, D::Error> {
+    struct SizeVisitor;
+
+    impl de::Visitor<'_> for SizeVisitor {
+        type Value = u64;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "a number of bytes or a size like \"250MB\"")
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<u64, E> {
+            match value {
+                0 => Err(E::custom("size must be greater than zero")),
+                bytes => Ok(bytes),
+            }
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<u64, E> {
+            match u64::try_from(value) {
+                Ok(bytes) => self.visit_u64(bytes),
+                Err(_) => Err(E::custom("size must be greater than zero")),
+            }
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<u64, E> {
+            parse_size(value).map_err(E::custom)
+        }
+    }
+
+    deserializer.deserialize_any(SizeVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_unit_suffix() {
+        let cases = [
+            ("512", 512),
+            ("512b", 512),
+            ("512k", 512 << 10),
+            ("512kb", 512 << 10),
+            ("512kib", 512 << 10),
+            ("250m", 250 << 20),
+            ("250mb", 250 << 20),
+            ("250mib", 250 << 20),
+            ("2g", 2 << 30),
+            ("2gb", 2 << 30),
+            ("2gib", 2 << 30),
+            ("1t", 1 << 40),
+            ("1tb", 1 << 40),
+            ("1tib", 1 << 40),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_size(input), Ok(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn units_are_case_insensitive_and_may_be_spaced() {
+        for input in ["250MB", "250Mb", "250mB", "250MiB", "250 MB", " 250mb "] {
+            assert_eq!(parse_size(input), Ok(250 << 20), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn fractions_round_to_whole_bytes() {
+        assert_eq!(parse_size("1.5GiB"), Ok(3 << 29));
+        assert_eq!(parse_size("0.5k"), Ok(512));
+        assert_eq!(parse_size("1.0001k"), Ok(1024));
+    }
+
+    #[test]
+    fn zero_and_garbage_are_rejected() {
+        for input in ["0", "0kb", "0.0001b"] {
+            assert_eq!(
+                parse_size(input),
+                Err("size must be greater than zero".to_string()),
+                "{}",
+                input
+            );
+        }
+        assert_eq!(
+            parse_size("10 parsecs"),
+            Err("unknown size unit \"parsecs\"".to_string())
+        );
+        assert_eq!(
+            parse_size("MB"),
+            Err("expected a size like \"250MB\", got \"MB\"".to_string())
+        );
+        assert_eq!(
+            parse_size("1.2.3mb"),
+            Err("expected a size like \"250MB\", got \"1.2.3mb\"".to_string())
+        );
+        assert_eq!(
+            parse_size("20000000tb"),
+            Err("size \"20000000tb\" is too large".to_string())
+        );
+    }
+}
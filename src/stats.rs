@@ -0,0 +1,130 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::logger::LogLevel;
+
+const LEVELS: usize = LogLevel::Off as usize + 1;
+
+/// A snapshot of a logger's counters, taken by Logger::stats().
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LoggerStats {
+    written: [u64; LEVELS],
+    filtered: [u64; LEVELS],
+    bytes_written: u64,
+    write_errors: u64,
+    rotation_errors: u64,
+    fallback_writes: u64,
+    truncated_bytes: u64,
+    sink_dropped: u64,
+}
+
+impl LoggerStats {
+    /// Records that passed the level check, whether or not the write succeeded.
+    pub fn written(&self, level: LogLevel) -> u64 {
+        self.written[level as usize]
+    }
+
+    /// Records skipped because their level was below the threshold. `log` crate
+    /// records below its global max level never reach the logger, so they aren't
+    /// counted here.
+    pub fn filtered(&self, level: LogLevel) -> u64 {
+        self.filtered[level as usize]
+    }
+
+    /// Records written at any level.
+    pub fn total_written(&self) -> u64 {
+        self.written.iter().sum()
+    }
+
+    /// Bytes written to the main output and sinks.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Failed writes to the main output or a sink.
+    pub fn write_errors(&self) -> u64 {
+        self.write_errors
+    }
+
+    /// Rotations that failed, e.g. because the next file couldn't be created.
+    /// The record that triggered one still goes into the current file.
+    pub fn rotation_errors(&self) -> u64 {
+        self.rotation_errors
+    }
+
+    /// Records written to stderr because the output was failing.
+    pub fn fallback_writes(&self) -> u64 {
+        self.fallback_writes
+    }
+
+    /// Message and field bytes cut off by the length limits.
+    pub fn truncated_bytes(&self) -> u64 {
+        self.truncated_bytes
+    }
+
+    /// Records network sinks discarded while their endpoint was unreachable, or
+    /// that it rejected.
+    pub fn sink_dropped(&self) -> u64 {
+        self.sink_dropped
+    }
+}
+
+// Relaxed atomics are enough: each counter is independent and only read as a
+// rough snapshot.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    written: [AtomicU64; LEVELS],
+    filtered: [AtomicU64; LEVELS],
+    bytes_written: AtomicU64,
+    write_errors: AtomicU64,
+    rotation_errors: AtomicU64,
+    fallback_writes: AtomicU64,
+    truncated_bytes: AtomicU64,
+}
+
+impl Counters {
+    pub(crate) fn record_written(&self, level: LogLevel) {
+        self.written[level as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_filtered(&self, level: LogLevel) {
+        self.filtered[level as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_bytes(&self, bytes: usize) {
+        self.bytes_written
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_error(&self) {
+        self.write_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_rotation_error(&self) {
+        self.rotation_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_fallback(&self) {
+        self.fallback_writes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_truncated(&self, bytes: usize) {
+        if bytes > 0 {
+            self.truncated_bytes
+                .fetch_add(bytes as u64, Ordering::Relaxed);
+        }
+    }
+
+    // Sink drops are counted by the sinks themselves and passed in.
+    pub(crate) fn snapshot(&self, sink_dropped: u64) -> LoggerStats {
+        LoggerStats {
+            written: self.written.each_ref().map(|n| n.load(Ordering::Relaxed)),
+            filtered: self.filtered.each_ref().map(|n| n.load(Ordering::Relaxed)),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            write_errors: self.write_errors.load(Ordering::Relaxed),
+            rotation_errors: self.rotation_errors.load(Ordering::Relaxed),
+            fallback_writes: self.fallback_writes.load(Ordering::Relaxed),
+            truncated_bytes: self.truncated_bytes.load(Ordering::Relaxed),
+            sink_dropped,
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use std::{
+    env, io,
+    net::{SocketAddr, UdpSocket},
+    path::PathBuf,
+};
+
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+
+use chrono::SecondsFormat;
+
+use crate::{host::HostInfo, logger::LogLevel, record::Record};
+
+// Structured data IDs need an enterprise number; 32473 is the one RFC 5612
+// reserves for documentation and examples.
+const FIELDS_SD_ID: &str = "fields@32473";
+
+/// The syslog facility, numbered as in RFC 5424.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Facility {
+    Kern = 0,
+    User = 1,
+    Mail = 2,
+    Daemon = 3,
+    Auth = 4,
+    Syslog = 5,
+    Lpr = 6,
+    News = 7,
+    Uucp = 8,
+    Cron = 9,
+    Authpriv = 10,
+    Ftp = 11,
+    Local0 = 16,
+    Local1 = 17,
+    Local2 = 18,
+    Local3 = 19,
+    Local4 = 20,
+    Local5 = 21,
+    Local6 = 22,
+    Local7 = 23,
+}
+
+/// How syslog datagrams are sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyslogTransport {
+    Udp(SocketAddr),
+    /// A local Unix datagram socket, usually /dev/log.
+    Unix(PathBuf),
+}
+
+/// Where and how a syslog sink sends records. Defaults to the user facility and
+/// the executable's file name as app name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyslogConfig {
+    transport: SyslogTransport,
+    facility: Facility,
+    app_name: String,
+}
+
+impl SyslogConfig {
+    pub fn new(transport: SyslogTransport) -> Self {
+        let app_name = env::current_exe()
+            .ok()
+            .and_then(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "-".to_string());
+        Self {
+            transport,
+            facility: Facility::User,
+            app_name,
+        }
+    }
+
+    /// A remote syslog server on `addr`.
+    pub fn udp(addr: SocketAddr) -> Self {
+        Self::new(SyslogTransport::Udp(addr))
+    }
+
+    /// The local syslog daemon's socket.
+    pub fn local() -> Self {
+        Self::new(SyslogTransport::Unix(PathBuf::from("/dev/log")))
+    }
+
+    pub(crate) fn transport(&self) -> &SyslogTransport {
+        &self.transport
+    }
+
+    pub fn with_facility(mut self, facility: Facility) -> Self {
+        self.facility = facility;
+        self
+    }
+
+    pub fn with_app_name(mut self, app_name: String) -> Self {
+        self.app_name = app_name;
+        self
+    }
+
+    // An RFC 5424 message: `<PRI>1 TIMESTAMP HOST APP PROCID - [SD] MSG`, with the
+    // record's fields as structured data.
+    pub(crate) fn format(&self, record: &Record, host: &HostInfo) -> String {
+        let priority = self.facility as u8 * 8 + severity(record.level);
+        let mut line = format!(
+            "<{}>1 {} {} {} {} - ",
+            priority,
+            record
+                .timestamp
+                .to_rfc3339_opts(SecondsFormat::Micros, true),
+            header_field(&host.name, 255),
+            header_field(&self.app_name, 48),
+            host.pid
+        );
+        if record.fields.is_empty() {
+            line.push('-');
+        } else {
+            line.push('[');
+            line.push_str(FIELDS_SD_ID);
+            for (key, value) in record.fields {
+                line.push(' ');
+                line.push_str(&sd_name(key));
+                line.push_str("=\"");
+                for c in value.to_string().chars() {
+                    if matches!(c, '"' | '\\' | ']') {
+                        line.push('\\');
+                    }
+                    line.push(c);
+                }
+                line.push('"');
+            }
+            line.push(']');
+        }
+        line.push(' ');
+        line.push_str(record.message);
+        line
+    }
+}
+
+// Trace has no syslog severity of its own and shares Debug's.
+pub(crate) fn severity(level: LogLevel) -> u8 {
+    match level {
+        LogLevel::Trace | LogLevel::Debug => 7,
+        LogLevel::Info => 6,
+        LogLevel::Warn => 4,
+        LogLevel::Error => 3,
+        LogLevel::Critical | LogLevel::Off => 2,
+    }
+}
+
+// Header fields are printable ASCII without spaces; "-" stands for empty.
+fn header_field(value: &str, max_len: usize) -> String {
+    let value: String = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_len)
+        .collect();
+    if value.is_empty() {
+        "-".to_string()
+    } else {
+        value
+    }
+}
+
+// Parameter names can't contain '=', ' ', ']' or '"' and are at most 32 chars.
+fn sd_name(key: &str) -> String {
+    let name: String = key
+        .chars()
+        .take(32)
+        .map(|c| match c {
+            '=' | ']' | '"' => '_',
+            c if c.is_ascii_graphic() => c,
+            _ => '_',
+        })
+        .collect();
+    if name.is_empty() {
+        "_".to_string()
+    } else {
+        name
+    }
+}
+
+enum Socket {
+    Udp(UdpSocket),
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+}
+
+// The socket is opened on first use and dropped after a failed send, so the
+// next record starts over with a fresh one.
+pub(crate) struct SyslogWriter {
+    transport: SyslogTransport,
+    socket: Option<Socket>,
+}
+
+impl SyslogWriter {
+    pub(crate) fn new(transport: SyslogTransport) -> Self {
+        Self {
+            transport,
+            socket: None,
+        }
+    }
+
+    pub(crate) fn send(&mut self, message: &str) -> io::Result<()> {
+        let socket = match self.socket.take() {
+            Some(socket) => socket,
+            None => self.open()?,
+        };
+        let sent = match (&socket, &self.transport) {
+            (Socket::Udp(socket), SyslogTransport::Udp(addr)) => {
+                socket.send_to(message.as_bytes(), addr)
+            }
+            #[cfg(unix)]
+            (Socket::Unix(socket), SyslogTransport::Unix(path)) => {
+                socket.send_to(message.as_bytes(), path)
+            }
+            _ => unreachable!("socket is opened for its transport"),
+        };
+        sent?;
+        self.socket = Some(socket);
+        Ok(())
+    }
+
+    fn open(&self) -> io::Result<Socket> {
+        match &self.transport {
+            SyslogTransport::Udp(addr) => {
+                let local: SocketAddr = match addr {
+                    SocketAddr::V4(_) => ([0, 0, 0, 0], 0).into(),
+                    SocketAddr::V6(_) => ([0u16; 8], 0).into(),
+                };
+                UdpSocket::bind(local).map(Socket::Udp)
+            }
+            #[cfg(unix)]
+            SyslogTransport::Unix(_) => UnixDatagram::unbound().map(Socket::Unix),
+            #[cfg(not(unix))]
+            SyslogTransport::Unix(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Unix syslog sockets need a unix platform",
+            )),
+        }
+    }
+}
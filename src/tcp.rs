@@ -0,0 +1,167 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+    net::{SocketAddr, TcpStream},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex, MutexGuard, PoisonError,
+    },
+    thread,
+    time::Duration,
+};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const FIRST_RETRY: Duration = Duration::from_millis(100);
+const MAX_RETRY: Duration = Duration::from_secs(30);
+
+struct Queue {
+    lines: VecDeque<String>,
+    closed: bool,
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    changed: Condvar,
+    capacity: usize,
+    dropped: AtomicU64,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// Ships records to a TCP endpoint from a thread of its own, so a slow or dead
+// endpoint never holds up logging. Records queue up while disconnected; past
+// the capacity the oldest ones are dropped and counted.
+pub(crate) struct TcpWriter {
+    shared: Arc<Shared>,
+}
+
+impl TcpWriter {
+    pub(crate) fn spawn(addr: SocketAddr, capacity: usize) -> Self {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue {
+                lines: VecDeque::new(),
+                closed: false,
+            }),
+            changed: Condvar::new(),
+            capacity: capacity.max(1),
+            dropped: AtomicU64::new(0),
+        });
+        let thread_shared = Arc::clone(&shared);
+        // If the thread can't start, records just pile up and get dropped.
+        let _ = thread::Builder::new()
+            .name("logtar-tcp".to_string())
+            .spawn(move || run(addr, &thread_shared));
+        Self { shared }
+    }
+
+    pub(crate) fn push(&self, line: &str) {
+        let mut queue = self.shared.lock();
+        if queue.lines.len() >= self.shared.capacity {
+            queue.lines.pop_front();
+            self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        queue.lines.push_back(line.to_string());
+        self.shared.changed.notify_one();
+    }
+
+    pub(crate) fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+// The thread sends whatever is still queued if it is connected, then stops.
+impl Drop for TcpWriter {
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.changed.notify_one();
+    }
+}
+
+fn run(addr: SocketAddr, shared: &Shared) {
+    let mut stream: Option<TcpStream> = None;
+    let mut backoff = FIRST_RETRY;
+    loop {
+        let lines = {
+            let mut queue = shared.lock();
+            while queue.lines.is_empty() && !queue.closed {
+                queue = shared
+                    .changed
+                    .wait(queue)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+            if queue.lines.is_empty() {
+                return;
+            }
+            std::mem::take(&mut queue.lines)
+        };
+
+        let connection = match stream.take().filter(is_open) {
+            Some(connection) => Ok(connection),
+            None => connect(addr),
+        };
+        let unsent = match connection {
+            Ok(mut connection) => match send(&mut connection, lines) {
+                Ok(()) => {
+                    backoff = FIRST_RETRY;
+                    stream = Some(connection);
+                    continue;
+                }
+                Err(unsent) => unsent,
+            },
+            Err(_) => lines,
+        };
+
+        // Put the unsent records back in front of anything queued meanwhile,
+        // still within the capacity, and wait before reconnecting.
+        {
+            let mut queue = shared.lock();
+            let newer = std::mem::replace(&mut queue.lines, unsent);
+            queue.lines.extend(newer);
+            while queue.lines.len() > shared.capacity {
+                queue.lines.pop_front();
+                shared.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            if queue.closed {
+                return;
+            }
+        }
+        thread::sleep(backoff);
+        backoff = (backoff * 2).min(MAX_RETRY);
+    }
+}
+
+fn connect(addr: SocketAddr) -> io::Result<TcpStream> {
+    let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+    stream.set_write_timeout(Some(CONNECT_TIMEOUT))?;
+    Ok(stream)
+}
+
+// Writes to a connection the endpoint already closed can still succeed once,
+// losing the record, so closed connections are caught before sending.
+fn is_open(stream: &TcpStream) -> bool {
+    if stream.set_nonblocking(true).is_err() {
+        return false;
+    }
+    let open = match stream.peek(&mut [0u8; 1]) {
+        Ok(0) => false,
+        Ok(_) => true,
+        Err(e) => e.kind() == io::ErrorKind::WouldBlock,
+    };
+    stream.set_nonblocking(false).is_ok() && open
+}
+
+// On failure, returns the records from the one that failed onwards. That one
+// may have been partly sent, so the endpoint can see it twice.
+fn send(stream: &mut TcpStream, mut lines: VecDeque<String>) -> Result<(), VecDeque<String>> {
+    while let Some(line) = lines.front() {
+        if stream.write_all(line.as_bytes()).is_err() {
+            return Err(lines);
+        }
+        lines.pop_front();
+    }
+    stream.flush().map_err(|_| VecDeque::new())
+}
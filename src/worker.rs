@@ -0,0 +1,186 @@
+use std::{
+    collections::VecDeque,
+    fmt, io,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc, Condvar, Mutex, MutexGuard, PoisonError,
+    },
+    thread::{self, JoinHandle},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    logger::{LogLevel, LoggerInner, OverflowPolicy},
+    sink::Lines,
+};
+
+pub(crate) enum Message {
+    Record {
+        level: LogLevel,
+        lines: Lines,
+        now: DateTime<Utc>,
+    },
+    Flush(mpsc::Sender<()>),
+}
+
+struct Queue {
+    messages: VecDeque<Message>,
+    records: usize,
+    closed: bool,
+}
+
+// A bounded queue between the logging threads and the writer thread. Only
+// records count towards the capacity; flush requests are always accepted.
+struct Channel {
+    queue: Mutex<Queue>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    capacity: usize,
+    policy: OverflowPolicy,
+    dropped: AtomicU64,
+}
+
+impl Channel {
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push(&self, message: Message) {
+        let mut queue = self.lock();
+        if let Message::Record { .. } = message {
+            while queue.records >= self.capacity {
+                match self.policy {
+                    OverflowPolicy::Block => {
+                        queue = self
+                            .not_full
+                            .wait(queue)
+                            .unwrap_or_else(PoisonError::into_inner);
+                    }
+                    OverflowPolicy::DropOldest => {
+                        let oldest = queue
+                            .messages
+                            .iter()
+                            .position(|m| matches!(m, Message::Record { .. }));
+                        if let Some(i) = oldest {
+                            queue.messages.remove(i);
+                            queue.records -= 1;
+                            self.dropped.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                }
+            }
+            queue.records += 1;
+        }
+        queue.messages.push_back(message);
+        self.not_empty.notify_one();
+    }
+
+    // Blocks until there is something to do. Returns None once the channel is
+    // closed and fully drained.
+    fn pop_all(&self) -> Option<VecDeque<Message>> {
+        let mut queue = self.lock();
+        while queue.messages.is_empty() {
+            if queue.closed {
+                return None;
+            }
+            queue = self
+                .not_empty
+                .wait(queue)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        queue.records = 0;
+        self.not_full.notify_all();
+        Some(std::mem::take(&mut queue.messages))
+    }
+
+    fn close(&self) {
+        self.lock().closed = true;
+        self.not_empty.notify_all();
+    }
+}
+
+pub(crate) struct Worker {
+    channel: Arc<Channel>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl fmt::Debug for Worker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Worker")
+            .field("capacity", &self.channel.capacity)
+            .field("policy", &self.channel.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Worker {
+    pub(crate) fn spawn(
+        inner: Arc<LoggerInner>,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> io::Result<Self> {
+        let channel = Arc::new(Channel {
+            queue: Mutex::new(Queue {
+                messages: VecDeque::new(),
+                records: 0,
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            capacity: capacity.max(1),
+            policy,
+            dropped: AtomicU64::new(0),
+        });
+
+        let thread_channel = Arc::clone(&channel);
+        let thread = thread::Builder::new()
+            .name("logtar-writer".to_string())
+            .spawn(move || {
+                while let Some(messages) = thread_channel.pop_all() {
+                    for message in messages {
+                        match message {
+                            Message::Record { level, lines, now } => {
+                                inner.write_formatted(level, &lines, now)
+                            }
+                            Message::Flush(done) => {
+                                inner.flush_output();
+                                let _ = done.send(());
+                            }
+                        }
+                    }
+                }
+                inner.flush_output();
+            })?;
+
+        Ok(Self {
+            channel,
+            thread: Some(thread),
+        })
+    }
+
+    pub(crate) fn send(&self, message: Message) {
+        self.channel.push(message);
+    }
+
+    // Waits until every record queued before this call has been written.
+    pub(crate) fn flush(&self) {
+        let (done, wait) = mpsc::channel();
+        self.send(Message::Flush(done));
+        let _ = wait.recv();
+    }
+
+    pub(crate) fn dropped_count(&self) -> u64 {
+        self.channel.dropped.load(Ordering::Relaxed)
+    }
+}
+
+// Dropped together with the last Logger clone: drains whatever is still queued.
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.channel.close();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
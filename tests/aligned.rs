@@ -0,0 +1,98 @@
+mod common;
+
+use std::{path::Path, sync::Arc};
+
+use chrono::{TimeDelta, TimeZone, Utc};
+use common::{log_files, read};
+use logger::{LogConfig, Logger, ManualClock, RollingConfig, RollingTimeOptions, TimeAlignment};
+
+fn aligned_config(dir: &Path, period: RollingTimeOptions, clock: &ManualClock) -> LogConfig {
+    LogConfig::new()
+        .with_directory(dir.to_path_buf())
+        .with_file_prefix("App_".to_string())
+        .with_rolling_config(
+            RollingConfig::new()
+                .with_time_threshold(period)
+                .with_time_alignment(TimeAlignment::Aligned),
+        )
+        .with_clock(Arc::new(clock.clone()))
+}
+
+fn names(dir: &Path) -> Vec<String> {
+    log_files(dir)
+        .iter()
+        .map(|path| common::file_name(path))
+        .collect()
+}
+
+#[test]
+fn daily_files_roll_at_midnight() {
+    let dir = tempfile::tempdir().unwrap();
+    // Started late in the day, well off any multiple of 24 hours since.
+    let clock = ManualClock::new(Utc.with_ymd_and_hms(2024, 5, 1, 23, 59, 0).unwrap());
+    let logger = Logger::new(Some(aligned_config(
+        dir.path(),
+        RollingTimeOptions::Daily,
+        &clock,
+    )));
+    logger.info("last minute of May 1st");
+    clock.advance(TimeDelta::seconds(59));
+    logger.info("last second of May 1st");
+    clock.advance(TimeDelta::seconds(1));
+    logger.info("midnight");
+    clock.advance(TimeDelta::hours(23));
+    logger.info("late on May 2nd");
+    drop(logger);
+
+    assert_eq!(
+        names(dir.path()),
+        ["App_2024-05-01.log", "App_2024-05-02.log"]
+    );
+    let files = log_files(dir.path());
+    assert_eq!(read(&files[0]).lines().count(), 2);
+    assert_eq!(read(&files[1]).lines().count(), 2);
+}
+
+#[test]
+fn hourly_files_roll_at_the_top_of_the_hour() {
+    let dir = tempfile::tempdir().unwrap();
+    let clock = ManualClock::new(Utc.with_ymd_and_hms(2024, 12, 31, 22, 45, 0).unwrap());
+    let logger = Logger::new(Some(aligned_config(
+        dir.path(),
+        RollingTimeOptions::Hourly,
+        &clock,
+    )));
+    for _ in 0..4 {
+        logger.info("tick");
+        clock.advance(TimeDelta::minutes(30));
+    }
+    drop(logger);
+
+    assert_eq!(
+        names(dir.path()),
+        [
+            "App_2024-12-31T22.log",
+            "App_2024-12-31T23.log",
+            "App_2025-01-01T00.log",
+        ]
+    );
+}
+
+#[test]
+fn a_start_mid_period_writes_into_the_current_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let clock = ManualClock::new(Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap());
+    let config = aligned_config(dir.path(), RollingTimeOptions::Daily, &clock);
+    Logger::new(Some(config.clone())).info("morning");
+    clock.advance(TimeDelta::hours(8));
+    Logger::new(Some(config.clone())).info("afternoon");
+    clock.advance(TimeDelta::hours(8));
+    Logger::new(Some(config)).info("next day");
+
+    assert_eq!(
+        names(dir.path()),
+        ["App_2024-05-01.log", "App_2024-05-02.log"]
+    );
+    let first = read(&log_files(dir.path())[0]);
+    assert!(first.contains("morning") && first.contains("afternoon"));
+}
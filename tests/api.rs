@@ -0,0 +1,21 @@
+// Everything a caller needs is at the crate root, so a glob import has to work
+// without clashing with the `logger` crate name.
+use logger::*;
+
+#[test]
+fn default_logger_writes_under_the_working_directory() {
+    // The only test in this binary, so changing the working directory can't
+    // affect another one.
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_current_dir(dir.path()).unwrap();
+
+    let logger = Logger::new(None);
+    logger.info("Hello from Logtar!");
+    info!(logger, "and from the macro");
+    let path = logger.current_file_path().unwrap();
+    drop(logger);
+
+    assert!(path.starts_with(dir.path().canonicalize().unwrap().join("logs")));
+    let contents = std::fs::read_to_string(path).unwrap();
+    assert_eq!(contents.lines().count(), 2);
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    sync::{Arc, Condvar, Mutex},
+    thread,
+};
+
+use logger::{InMemoryWriter, LogConfig, Logger, OverflowPolicy};
+
+const THREADS: usize = 8;
+const PER_THREAD: usize = 500;
+
+fn async_config(policy: OverflowPolicy, capacity: usize) -> LogConfig {
+    LogConfig::new()
+        .with_async(true)
+        .with_buffer_capacity(capacity)
+        .with_overflow_policy(policy)
+}
+
+// Each thread's records in the order they were written.
+fn by_thread(contents: &str) -> HashMap<usize, Vec<usize>> {
+    let mut seen: HashMap<usize, Vec<usize>> = HashMap::new();
+    for line in contents.lines() {
+        let message = line.rsplit_once("] ").unwrap().1;
+        let (thread, index) = message.split_once(':').unwrap();
+        seen.entry(thread.parse().unwrap())
+            .or_default()
+            .push(index.parse().unwrap());
+    }
+    seen
+}
+
+fn hammer(logger: &Logger) {
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            scope.spawn(move || {
+                for i in 0..PER_THREAD {
+                    logger.info(&format!("{}:{}", t, i));
+                }
+            });
+        }
+    });
+}
+
+#[test]
+fn block_mode_loses_nothing() {
+    let writer = InMemoryWriter::new();
+    let config = async_config(OverflowPolicy::Block, 4);
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    hammer(&logger);
+    logger.flush();
+
+    assert_eq!(logger.dropped_count(), 0);
+    let seen = by_thread(&writer.contents_string());
+    assert_eq!(seen.len(), THREADS);
+    for indices in seen.values() {
+        assert_eq!(*indices, (0..PER_THREAD).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn dropping_the_logger_drains_the_queue() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = common::file_config(dir.path());
+    let config = config
+        .with_async(true)
+        .with_buffer_capacity(16)
+        .with_overflow_policy(OverflowPolicy::Block);
+    let logger = Logger::new(Some(config));
+    hammer(&logger);
+    let path = logger.current_file_path().unwrap();
+    drop(logger);
+
+    let contents = common::read(&path);
+    assert_eq!(contents.lines().count(), THREADS * PER_THREAD);
+}
+
+// Holds the writer thread inside its first write until released.
+#[derive(Clone, Default)]
+struct Gate {
+    state: Arc<(Mutex<(bool, bool)>, Condvar)>,
+    inner: InMemoryWriter,
+}
+
+impl Gate {
+    fn wait_until_entered(&self) {
+        let (lock, condvar) = &*self.state;
+        let _entered = condvar
+            .wait_while(lock.lock().unwrap(), |(entered, _)| !*entered)
+            .unwrap();
+    }
+
+    fn open(&self) {
+        let (lock, condvar) = &*self.state;
+        lock.lock().unwrap().1 = true;
+        condvar.notify_all();
+    }
+}
+
+impl Write for Gate {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let (lock, condvar) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.0 = true;
+        condvar.notify_all();
+        let _open = condvar.wait_while(state, |(_, open)| !*open).unwrap();
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[test]
+fn drop_oldest_keeps_the_newest_records() {
+    let gate = Gate::default();
+    let config = async_config(OverflowPolicy::DropOldest, 2);
+    let logger = Logger::with_writer(Some(config), Box::new(gate.clone()));
+    logger.info("0:0");
+    gate.wait_until_entered();
+    for i in 1..=100 {
+        logger.info(&format!("0:{}", i));
+    }
+    gate.open();
+    logger.flush();
+
+    // The first record was already being written; of the rest only the
+    // newest two fit in the queue.
+    assert_eq!(logger.dropped_count(), 98);
+    assert_eq!(by_thread(&gate.inner.contents_string())[&0], [0, 99, 100]);
+}
@@ -0,0 +1,93 @@
+use std::{fs, path::Path};
+
+use logger::{ChainError, LogConfig, Logger, RollingConfig};
+
+fn audit_logger(dir: &Path, rolling_config: RollingConfig) -> Logger {
+    let config = LogConfig::new()
+        .with_directory(dir.to_path_buf())
+        .with_rolling_config(rolling_config)
+        .with_audit(true);
+    Logger::new(Some(config))
+}
+
+#[test]
+fn clean_file_verifies() {
+    let dir = tempfile::tempdir().unwrap();
+    let logger = audit_logger(dir.path(), RollingConfig::new());
+    for i in 0..5 {
+        logger.info(&format!("record {}", i));
+    }
+    let path = logger.current_file_path().unwrap();
+    drop(logger);
+
+    let report = Logger::verify_file(&path).unwrap();
+    assert_eq!(report.records, 5);
+    assert_eq!(report.next_file, None);
+}
+
+#[test]
+fn one_byte_edit_is_found_at_its_line() {
+    let dir = tempfile::tempdir().unwrap();
+    let logger = audit_logger(dir.path(), RollingConfig::new());
+    for i in 0..5 {
+        logger.info(&format!("record {}", i));
+    }
+    let path = logger.current_file_path().unwrap();
+    drop(logger);
+
+    // Line 1 is the genesis, so "record 2" is on line 4.
+    let mut contents = fs::read(&path).unwrap();
+    let at = contents
+        .windows(8)
+        .position(|window| window == b"record 2")
+        .unwrap();
+    contents[at + 7] = b'9';
+    fs::write(&path, contents).unwrap();
+
+    assert!(matches!(
+        Logger::verify_file(&path),
+        Err(ChainError::Corrupted { line: 4 })
+    ));
+}
+
+#[test]
+fn rotated_files_chain_into_each_other() {
+    let dir = tempfile::tempdir().unwrap();
+    let logger = audit_logger(dir.path(), RollingConfig::new().with_size_bytes(200));
+    for i in 0..3 {
+        logger.info(&format!("record {} {}", i, "x".repeat(100)));
+    }
+    let last = logger.current_file_path().unwrap();
+    drop(logger);
+
+    let mut files: Vec<_> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .collect();
+    files.sort();
+    let first = Logger::verify_file(&files[0]).unwrap();
+    assert_eq!(first.records, 1);
+    let next = first.next_file.unwrap();
+    assert_eq!(next, files[1]);
+    assert_eq!(Logger::verify_file(&next).unwrap().genesis, first.head);
+    assert_eq!(files.last(), Some(&last));
+}
+
+#[test]
+fn file_recreated_after_a_rename_verifies() {
+    let dir = tempfile::tempdir().unwrap();
+    let logger = audit_logger(dir.path(), RollingConfig::new());
+    logger.info("before");
+    let path = logger.current_file_path().unwrap();
+    let moved = dir.path().join("moved.audit");
+    fs::rename(&path, &moved).unwrap();
+    logger.reopen().unwrap();
+    logger.info("after");
+    drop(logger);
+
+    let old = Logger::verify_file(&moved).unwrap();
+    let new = Logger::verify_file(&path).unwrap();
+    assert_eq!(new.records, 1);
+    assert_eq!(new.genesis, old.head);
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::{file_config, log_files, read};
+use logger::Logger;
+
+#[test]
+fn parent_and_child_share_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let parent = Logger::new(Some(config));
+    let child = parent.with_fields(&[
+        ("request_id", "abc".into()),
+        ("client_ip", "10.0.0.1".into()),
+    ]);
+    parent.info("parent before");
+    child.info("child");
+    parent.info("parent after");
+    assert_eq!(parent.current_file_path(), child.current_file_path());
+    drop(child);
+    drop(parent);
+
+    let files = log_files(dir.path());
+    assert_eq!(files.len(), 1);
+    let contents = read(&files[0]);
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with("] parent before"));
+    assert!(lines[1].ends_with("] child request_id=abc client_ip=10.0.0.1"));
+    assert!(lines[2].ends_with("] parent after"));
+}
+
+#[test]
+fn nested_children_merge_and_override() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let root = Logger::new(Some(config));
+    let service = root.with_fields(&[("service", "api".into()), ("stage", "outer".into())]);
+    let request = service.with_fields(&[("stage", "inner".into()), ("request_id", 7.into())]);
+    request.info("nested");
+    service.info("middle");
+    let path = root.current_file_path().unwrap();
+    drop((root, service, request));
+
+    let contents = read(&path);
+    let lines: Vec<&str> = contents.lines().collect();
+    assert!(lines[0].ends_with("] nested service=api stage=inner request_id=7"));
+    assert!(lines[1].ends_with("] middle service=api stage=outer"));
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use std::sync::{
+    atomic::{AtomicI64, Ordering},
+    Arc,
+};
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use common::{file_config, log_files, start_time};
+use logger::{
+    Clock, InMemoryWriter, LogConfig, Logger, ManualClock, RollingConfig, RollingTimeOptions,
+};
+
+fn timestamps(writer: &InMemoryWriter) -> Vec<String> {
+    writer
+        .contents_string()
+        .lines()
+        .map(|line| line.split(' ').next().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn manual_clocks_only_move_when_told() {
+    let clock = ManualClock::new(start_time());
+    let shared = clock.clone();
+    assert_eq!(clock.now(), start_time());
+
+    clock.advance(TimeDelta::milliseconds(1500));
+    assert_eq!(shared.now(), start_time() + TimeDelta::milliseconds(1500));
+    shared.set(Utc.with_ymd_and_hms(2020, 6, 1, 12, 0, 0).unwrap());
+    assert_eq!(
+        clock.now(),
+        Utc.with_ymd_and_hms(2020, 6, 1, 12, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn timestamps_come_from_the_clock() {
+    let clock = ManualClock::new(start_time());
+    let config = LogConfig::new().with_clock(Arc::new(clock.clone()));
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    logger.info("start");
+    clock.advance(TimeDelta::milliseconds(250));
+    logger.info("later");
+    clock.set(start_time() - TimeDelta::days(1));
+    logger.info("earlier");
+
+    assert_eq!(
+        timestamps(&writer),
+        [
+            "2024-01-01T00:00:00.000Z",
+            "2024-01-01T00:00:00.250Z",
+            "2023-12-31T00:00:00.000Z",
+        ]
+    );
+}
+
+// Any Clock implementation works, not just ManualClock.
+struct Ticking(AtomicI64);
+
+impl Clock for Ticking {
+    fn now(&self) -> DateTime<Utc> {
+        let seconds = self.0.fetch_add(1, Ordering::Relaxed);
+        start_time() + TimeDelta::seconds(seconds)
+    }
+}
+
+#[test]
+fn custom_clocks_are_used() {
+    let config = LogConfig::new().with_clock(Arc::new(Ticking(AtomicI64::new(0))));
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    logger.info("one");
+    logger.info("two");
+
+    let stamps = timestamps(&writer);
+    assert!(stamps[0] < stamps[1], "{:?}", stamps);
+}
+
+#[test]
+fn a_year_of_rotation_runs_instantly() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, clock) = file_config(dir.path());
+    let rolling = RollingConfig::new().with_time_threshold(RollingTimeOptions::Daily);
+    let logger = Logger::new(Some(config.with_rolling_config(rolling)));
+    for _ in 0..366 {
+        logger.info("daily");
+        clock.advance(TimeDelta::days(1));
+    }
+    drop(logger);
+
+    assert_eq!(log_files(dir.path()).len(), 366);
+}
+
+#[test]
+fn configs_compare_clocks_by_identity() {
+    let clock: Arc<dyn Clock> = Arc::new(ManualClock::new(start_time()));
+    let a = LogConfig::new().with_clock(clock.clone());
+    let b = LogConfig::new().with_clock(clock);
+    let c = LogConfig::new().with_clock(Arc::new(ManualClock::new(start_time())));
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_ne!(a, LogConfig::new());
+    assert_eq!(LogConfig::new(), LogConfig::new());
+}
@@ -0,0 +1,54 @@
+// Helpers shared by the integration tests; not every test file uses all of them.
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use chrono::{DateTime, TimeZone, Utc};
+use logger::{LogConfig, ManualClock};
+
+pub fn start_time() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+}
+
+// A config writing to `dir` on a clock that only moves when told to.
+pub fn file_config(dir: &Path) -> (LogConfig, ManualClock) {
+    let clock = ManualClock::new(start_time());
+    let config = LogConfig::new()
+        .with_directory(dir.to_path_buf())
+        .with_file_prefix("App_".to_string())
+        .with_clock(Arc::new(clock.clone()));
+    (config, clock)
+}
+
+// The `.log` files in `dir`, oldest first. Files sharing a timestamp are
+// ordered by their `.N` sequence number.
+pub fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .filter(|path| !path.is_symlink())
+        .collect();
+    files.sort_by_key(|path| {
+        let stem = path.file_stem().unwrap().to_string_lossy().into_owned();
+        match stem.rsplit_once('.') {
+            Some((name, sequence)) if sequence.parse::<u32>().is_ok() => {
+                (name.to_string(), sequence.parse().unwrap())
+            }
+            _ => (stem, 0),
+        }
+    });
+    files
+}
+
+pub fn file_name(path: &Path) -> String {
+    path.file_name().unwrap().to_string_lossy().into_owned()
+}
+
+pub fn read(path: &Path) -> String {
+    fs::read_to_string(path).unwrap()
+}
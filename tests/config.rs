@@ -0,0 +1,245 @@
+use std::{fs, path::Path};
+
+use logger::{ConfigError, LogConfig, RollingConfig, RollingSizeOptions, RollingTimeOptions};
+use tempfile::TempDir;
+
+fn config_file(dir: &TempDir, name: &str, contents: &str) -> std::path::PathBuf {
+    let path = dir.path().join(name);
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn load_json(contents: &str) -> Result<LogConfig, ConfigError> {
+    let dir = tempfile::tempdir().unwrap();
+    LogConfig::from_json_file(&config_file(&dir, "config.json", contents))
+}
+
+#[test]
+fn a_missing_file_is_an_io_error() {
+    let path = Path::new("/nonexistent/logtar/config.json");
+    match LogConfig::from_json_file(path) {
+        Err(ConfigError::Io { path: reported, .. }) => assert_eq!(reported, path),
+        other => panic!("expected Io, got {:?}", other),
+    }
+}
+
+#[test]
+fn truncated_json_is_a_syntax_error() {
+    for contents in [
+        "",
+        r#"{ "level": 2"#,
+        r#"{ "rolling_config": { "size_threshold": "#,
+    ] {
+        assert!(
+            matches!(load_json(contents), Err(ConfigError::Json(_))),
+            "{:?}",
+            contents
+        );
+    }
+}
+
+#[test]
+fn wrong_types_name_the_key() {
+    let cases = [
+        ("[1, 2]", "<root>", "an object"),
+        (r#"{ "level": true }"#, "level", "a number or a string"),
+        (r#"{ "file_prefix": 5 }"#, "file_prefix", "a string"),
+        (r#"{ "rolling_config": [] }"#, "rolling_config", "an object"),
+        (
+            r#"{ "rolling_config": { "time_threshold": "hourly" } }"#,
+            "rolling_config.time_threshold",
+            "a positive number",
+        ),
+        (
+            r#"{ "rolling_config": { "size_threshold": -5 } }"#,
+            "rolling_config.size_threshold",
+            "a positive number",
+        ),
+    ];
+    for (contents, key, expected) in cases {
+        match load_json(contents) {
+            Err(ConfigError::WrongType {
+                key: reported,
+                expected: reported_expected,
+            }) => {
+                assert_eq!(reported, key, "{}", contents);
+                assert_eq!(reported_expected, expected, "{}", contents);
+            }
+            other => panic!("expected WrongType for {}, got {:?}", contents, other),
+        }
+    }
+}
+
+#[test]
+fn unknown_rolling_options_are_out_of_range() {
+    let cases = [
+        (
+            r#"{ "rolling_config": { "size_threshold": "5 parsecs" } }"#,
+            "rolling_config.size_threshold",
+            "unknown size unit \"parsecs\"",
+        ),
+        (
+            r#"{ "rolling_config": { "size_threshold": 0 } }"#,
+            "rolling_config.size_threshold",
+            "size must be greater than zero",
+        ),
+        (
+            r#"{ "rolling_config": { "time_threshold": 61 } }"#,
+            "rolling_config.time_threshold",
+            "Value does not match any known time option",
+        ),
+        (
+            r#"{ "level": 7 }"#,
+            "level",
+            "Unknown log level \"7\", expected one of trace, debug, info, warn, error, critical, \
+             off or 0..=6",
+        ),
+    ];
+    for (contents, key, reason) in cases {
+        match load_json(contents) {
+            Err(ConfigError::OutOfRange {
+                key: reported,
+                reason: reported_reason,
+            }) => {
+                assert_eq!(reported, key, "{}", contents);
+                assert_eq!(reported_reason, reason, "{}", contents);
+            }
+            other => panic!("expected OutOfRange for {}, got {:?}", contents, other),
+        }
+    }
+}
+
+#[test]
+fn bad_values_for_other_keys_name_the_key() {
+    match load_json(r#"{ "level": "warn", "console": "yes" }"#) {
+        Err(e @ ConfigError::OutOfRange { .. }) => {
+            assert!(
+                e.to_string().starts_with("Invalid value for console: "),
+                "{}",
+                e
+            )
+        }
+        other => panic!("expected OutOfRange, got {:?}", other),
+    }
+}
+
+#[test]
+fn unknown_keys_are_warnings() {
+    let config = load_json(
+        r#"{
+            "level": 1,
+            "colour": true,
+            "rolling_config": { "time_threshold": 3600, "max_size": 10 }
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.warnings(),
+        [
+            "Unknown config key \"colour\"",
+            "Unknown config key \"rolling_config.max_size\"",
+        ]
+    );
+}
+
+#[test]
+fn known_keys_produce_no_warnings() {
+    let config = load_json(
+        r#"{
+            "level": 1,
+            "file_prefix": "Svc_",
+            "console": true,
+            "rolling_config": { "time_threshold": 3600, "size_threshold": "1mb" }
+        }"#,
+    )
+    .unwrap();
+
+    assert!(config.warnings().is_empty());
+    let expected = LogConfig::new()
+        .with_file_prefix("Svc_".to_string())
+        .with_console(true)
+        .with_rolling_config(
+            RollingConfig::new()
+                .with_time_threshold(RollingTimeOptions::Hourly)
+                .with_size_bytes(1024 * 1024),
+        );
+    assert_eq!(config, expected);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn equivalent_toml_and_json_files_are_equal() {
+    let dir = tempfile::tempdir().unwrap();
+    let pairs = [
+        (
+            r#"{
+                "level": 2,
+                "file_prefix": "Svc_",
+                "rolling_config": { "size_threshold": 1048576, "time_threshold": 86400 }
+            }"#,
+            r#"
+                level = 2
+                file_prefix = "Svc_"
+
+                [rolling_config]
+                size_threshold = 1048576
+                time_threshold = 86400
+            "#,
+        ),
+        (
+            r#"{ "level": "debug", "rolling_config": { "size_threshold": "10mb" } }"#,
+            r#"
+                level = "DEBUG"
+                rolling_config = { size_threshold = "10MB" }
+            "#,
+        ),
+    ];
+    for (json, toml) in pairs {
+        let json = LogConfig::from_file(&config_file(&dir, "config.json", json)).unwrap();
+        let toml = LogConfig::from_file(&config_file(&dir, "config.toml", toml)).unwrap();
+        assert_eq!(json, toml);
+        assert_ne!(json, LogConfig::new());
+    }
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn invalid_toml_is_a_syntax_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = config_file(&dir, "config.toml", "level = ");
+    assert!(matches!(
+        LogConfig::from_toml_file(&path),
+        Err(ConfigError::Toml(_))
+    ));
+}
+
+#[test]
+fn other_extensions_are_unsupported() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = config_file(&dir, "config.yaml", "level: 2");
+    match LogConfig::from_file(&path) {
+        Err(ConfigError::UnsupportedFormat(reported)) => assert_eq!(reported, path),
+        other => panic!("expected UnsupportedFormat, got {:?}", other),
+    }
+}
+
+#[test]
+fn sizes_take_legacy_numbers_and_strings() {
+    let legacy = load_json(r#"{ "rolling_config": { "size_threshold": 10485760 } }"#).unwrap();
+    let string = load_json(r#"{ "rolling_config": { "size_threshold": "10MB" } }"#).unwrap();
+    let expected = LogConfig::new()
+        .with_rolling_config(RollingConfig::new().with_size_threshold(RollingSizeOptions::TenMB));
+    assert_eq!(legacy, expected);
+    assert_eq!(string, expected);
+
+    let serde: RollingConfig = serde_json::from_str(r#"{ "size_threshold": "250mb" }"#).unwrap();
+    assert_eq!(
+        serde,
+        RollingConfig::new().with_size_bytes(250 * 1024 * 1024)
+    );
+    let zero = serde_json::from_str::<RollingConfig>(r#"{ "size_threshold": 0 }"#).unwrap_err();
+    assert!(zero
+        .to_string()
+        .starts_with("size must be greater than zero"));
+}
@@ -0,0 +1,25 @@
+mod common;
+
+use logger::{InMemoryWriter, Logger, Sink};
+
+#[test]
+fn the_file_and_plain_sinks_stay_uncolored() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = common::file_config(dir.path());
+    let writer = InMemoryWriter::new();
+    let config = config
+        .with_console(true)
+        .with_color(true)
+        .add_sink(Sink::new(writer.clone()));
+    let logger = Logger::new(Some(config));
+    logger.warn("warned");
+    logger.error("failed");
+    logger.critical("down");
+    let path = logger.current_file_path().unwrap();
+    drop(logger);
+
+    let file = common::read(&path);
+    assert_eq!(file.lines().count(), 3);
+    assert!(!file.contains('\x1b'));
+    assert_eq!(writer.contents_string(), file);
+}
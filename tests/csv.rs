@@ -0,0 +1,135 @@
+use std::{fs, path::Path};
+
+use logger::{
+    InMemoryWriter, LogConfig, LogFormat, LogLevel, LogValue, Logger, RollingConfig, Sink,
+};
+
+fn csv_config(dir: &Path) -> LogConfig {
+    LogConfig::new()
+        .with_directory(dir.to_path_buf())
+        .with_format(LogFormat::Csv)
+        .with_csv_fields(vec!["user".to_string()])
+}
+
+fn read_rows(path: &Path) -> (Vec<String>, Vec<Vec<String>>) {
+    let mut reader = csv::Reader::from_path(path).unwrap();
+    let header = reader.headers().unwrap().iter().map(String::from).collect();
+    let rows = reader
+        .records()
+        .map(|row| row.unwrap().iter().map(String::from).collect())
+        .collect();
+    (header, rows)
+}
+
+fn log_files(dir: &Path) -> Vec<std::path::PathBuf> {
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn tricky_values_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let logger = Logger::new(Some(csv_config(dir.path())));
+    let message = "a, \"quoted\"\nsecond line";
+    logger.log_kv(
+        LogLevel::Warn,
+        message,
+        &[("user", LogValue::from("o'brien, \"jr\""))],
+    );
+    logger.info("plain");
+    let path = logger.current_file_path().unwrap();
+    drop(logger);
+
+    let (header, rows) = read_rows(&path);
+    assert_eq!(
+        header,
+        [
+            "timestamp",
+            "level",
+            "file",
+            "line",
+            "thread",
+            "message",
+            "user"
+        ]
+    );
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0][1], "WARN");
+    assert_eq!(rows[0][2], file!());
+    assert_eq!(rows[0][5], message);
+    assert_eq!(rows[0][6], "o'brien, \"jr\"");
+    assert_eq!(rows[1][5], "plain");
+    assert_eq!(rows[1][6], "");
+}
+
+#[test]
+fn every_rotated_file_gets_a_header() {
+    let dir = tempfile::tempdir().unwrap();
+    let config =
+        csv_config(dir.path()).with_rolling_config(RollingConfig::new().with_size_bytes(100));
+    let logger = Logger::new(Some(config));
+    for i in 0..3 {
+        logger.info(&format!("record {} {}", i, "x".repeat(60)));
+    }
+    drop(logger);
+
+    let files = log_files(dir.path());
+    assert_eq!(files.len(), 3);
+    for file in files {
+        let (header, rows) = read_rows(&file);
+        assert_eq!(header[0], "timestamp");
+        assert_eq!(rows.len(), 1);
+    }
+}
+
+#[test]
+fn file_recreated_after_a_rename_gets_a_header() {
+    let dir = tempfile::tempdir().unwrap();
+    let logger = Logger::new(Some(csv_config(dir.path())));
+    logger.info("before");
+    let path = logger.current_file_path().unwrap();
+    fs::rename(&path, dir.path().join("moved.csv")).unwrap();
+    logger.reopen().unwrap();
+    logger.info("after");
+    drop(logger);
+
+    let (header, rows) = read_rows(&path);
+    assert_eq!(header[0], "timestamp");
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0][5], "after");
+}
+
+#[test]
+fn csv_sinks_start_with_a_header() {
+    let sink = InMemoryWriter::new();
+    let config = LogConfig::new()
+        .with_csv_fields(vec!["user".to_string()])
+        .add_sink(Sink::new(sink.clone()).with_format(LogFormat::Csv));
+    let logger = Logger::with_writer(Some(config), Box::new(InMemoryWriter::new()));
+    logger.log_kv(LogLevel::Warn, "signed in", &[("user", "alice".into())]);
+    drop(logger);
+
+    let contents = sink.contents();
+    let mut reader = csv::Reader::from_reader(contents.as_slice());
+    assert_eq!(
+        reader.headers().unwrap(),
+        vec![
+            "timestamp",
+            "level",
+            "file",
+            "line",
+            "thread",
+            "message",
+            "user"
+        ]
+    );
+    let rows: Vec<_> = reader.records().map(Result::unwrap).collect();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(&rows[0][5], "signed in");
+    assert_eq!(&rows[0][6], "alice");
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use chrono::TimeDelta;
+use logger::{
+    info, warn, InMemoryWriter, LogConfig, LogLevel, LogValue, Logger, RollingConfig,
+    RollingTimeOptions,
+};
+
+fn dedup_logger() -> (Logger, InMemoryWriter) {
+    let writer = InMemoryWriter::new();
+    let config = LogConfig::new().with_dedup(true);
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    (logger, writer)
+}
+
+#[test]
+fn interleaved_runs_are_reported_separately() {
+    let (logger, writer) = dedup_logger();
+    for message in ["A", "A", "A", "B", "A"] {
+        info!(logger, "{}", message);
+    }
+    drop(logger);
+
+    let lines = writer.contents_string();
+    let lines: Vec<&str> = lines.lines().collect();
+    assert_eq!(lines.len(), 4, "{:?}", lines);
+    assert!(lines[0].ends_with("A"));
+    assert!(lines[1].contains("last message repeated 2 times"));
+    assert!(lines[2].ends_with("B"));
+    assert!(lines[3].ends_with("A"));
+}
+
+#[test]
+fn pending_repeats_are_written_on_drop() {
+    let (logger, writer) = dedup_logger();
+    for _ in 0..5 {
+        warn!(logger, "retrying");
+    }
+    assert_eq!(writer.contents_string().lines().count(), 1);
+    drop(logger);
+
+    let lines = writer.contents_string();
+    assert!(lines
+        .lines()
+        .nth(1)
+        .unwrap()
+        .contains("last message repeated 4 times"));
+}
+
+#[test]
+fn records_with_different_fields_are_not_collapsed() {
+    let (logger, writer) = dedup_logger();
+    for user_id in [1, 2, 2] {
+        let handle = logger.with_fields(&[("user_id", LogValue::from(user_id))]);
+        handle.info("login");
+    }
+    logger.log_kv(LogLevel::Info, "login", &[("user_id", LogValue::from(3))]);
+    drop(logger);
+
+    let lines = writer.contents_string();
+    let lines: Vec<&str> = lines.lines().collect();
+    assert_eq!(lines.len(), 4, "{:?}", lines);
+    assert!(lines[0].contains("user_id=1"));
+    assert!(lines[1].contains("user_id=2"));
+    assert!(lines[2].contains("last message repeated 1 times"));
+    assert!(lines[3].contains("user_id=3"));
+}
+
+#[test]
+fn same_record_from_another_target_is_not_collapsed() {
+    let (logger, writer) = dedup_logger();
+    info!(target: "app::db", logger, "slow query");
+    info!(target: "app::http", logger, "slow query");
+    drop(logger);
+
+    assert_eq!(writer.contents_string().lines().count(), 2);
+}
+
+fn messages(path: &std::path::Path) -> Vec<String> {
+    common::read(path)
+        .lines()
+        .map(|line| line.split_once("] ").unwrap().1.to_string())
+        .collect()
+}
+
+#[test]
+fn pending_repeats_go_into_the_file_before_a_time_rotation() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, clock) = common::file_config(dir.path());
+    let config = config.with_dedup(true).with_rolling_config(
+        RollingConfig::new().with_time_threshold(RollingTimeOptions::Minutely),
+    );
+    let logger = Logger::new(Some(config));
+    for _ in 0..5 {
+        info!(logger, "A");
+    }
+    clock.advance(TimeDelta::minutes(2));
+    info!(logger, "B");
+    drop(logger);
+
+    let files = common::log_files(dir.path());
+    assert_eq!(files.len(), 2);
+    assert_eq!(messages(&files[0]), ["A", "last message repeated 4 times"]);
+    assert_eq!(messages(&files[1]), ["B"]);
+}
+
+#[test]
+fn pending_repeats_go_into_the_file_before_a_size_rotation() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = common::file_config(dir.path());
+    // Room for one record but not its summary as well.
+    let config = config
+        .with_dedup(true)
+        .with_rolling_config(RollingConfig::new().with_size_bytes(100));
+    let logger = Logger::new(Some(config));
+    for _ in 0..3 {
+        info!(logger, "A");
+    }
+    info!(logger, "B");
+    drop(logger);
+
+    let files = common::log_files(dir.path());
+    assert_eq!(files.len(), 2);
+    assert_eq!(messages(&files[0]), ["A", "last message repeated 2 times"]);
+    assert_eq!(messages(&files[1]), ["B"]);
+}
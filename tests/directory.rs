@@ -0,0 +1,54 @@
+mod common;
+
+use std::{env, fs, path::PathBuf};
+
+use logger::{LogConfig, Logger, LoggerError};
+
+#[test]
+fn missing_parent_directories_are_created() {
+    let dir = tempfile::tempdir().unwrap();
+    let nested = dir.path().join("var").join("log").join("app");
+    let (config, _clock) = common::file_config(&nested);
+    let logger = Logger::new(Some(config));
+    logger.info("nested");
+
+    let path = logger.current_file_path().unwrap();
+    assert_eq!(path.parent(), Some(nested.as_path()));
+    drop(logger);
+    assert!(common::read(&path).ends_with("nested\n"));
+}
+
+#[test]
+fn an_uncreatable_directory_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let blocker = dir.path().join("not_a_dir");
+    fs::write(&blocker, "").unwrap();
+    let config = LogConfig::new().with_directory(blocker.join("logs"));
+
+    match Logger::try_new(Some(config)) {
+        Err(LoggerError::CreateDir { path, .. }) => assert_eq!(path, blocker.join("logs")),
+        other => panic!("expected CreateDir, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn relative_directories_resolve_against_the_working_directory_at_construction() {
+    // The other tests here only use absolute paths, so changing the working
+    // directory can't affect them.
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    env::set_current_dir(&root).unwrap();
+    let config = LogConfig::new().with_directory(PathBuf::from("relative/logs"));
+    let logger = Logger::new(Some(config));
+
+    fs::create_dir(root.join("elsewhere")).unwrap();
+    env::set_current_dir(root.join("elsewhere")).unwrap();
+    logger.info("still in the first directory");
+
+    let path = logger.current_file_path().unwrap();
+    assert!(path.is_absolute());
+    assert_eq!(path.parent(), Some(root.join("relative/logs").as_path()));
+    drop(logger);
+    assert!(common::read(&path).ends_with("still in the first directory\n"));
+    assert!(!root.join("elsewhere/relative").exists());
+}
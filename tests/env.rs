@@ -0,0 +1,97 @@
+// The environment is process-global, so everything that changes it lives in
+// this one test binary, in one test.
+use std::{env, path::PathBuf};
+
+use logger::{ConfigError, LogConfig, LogLevel, RollingConfig, RollingTimeOptions};
+
+const VARS: [&str; 5] = [
+    "LOGTAR_LEVEL",
+    "LOGTAR_FILE_PREFIX",
+    "LOGTAR_DIR",
+    "LOGTAR_ROLL_SIZE",
+    "LOGTAR_ROLL_TIME",
+];
+
+fn with_env(vars: &[(&str, &str)]) -> Result<LogConfig, ConfigError> {
+    for var in VARS {
+        env::remove_var(var);
+    }
+    for (var, value) in vars {
+        env::set_var(var, value);
+    }
+    LogConfig::from_env()
+}
+
+fn error_message(vars: &[(&str, &str)]) -> String {
+    match with_env(vars) {
+        Err(e @ ConfigError::InvalidEnv { .. }) => e.to_string(),
+        other => panic!("expected InvalidEnv, got {:?}", other),
+    }
+}
+
+#[test]
+fn from_env() {
+    // Nothing set keeps the defaults.
+    assert_eq!(with_env(&[]).unwrap(), LogConfig::new());
+
+    let config = with_env(&[
+        ("LOGTAR_LEVEL", "debug"),
+        ("LOGTAR_FILE_PREFIX", "Svc_"),
+        ("LOGTAR_DIR", "/var/log/svc"),
+        ("LOGTAR_ROLL_SIZE", "10mb"),
+        ("LOGTAR_ROLL_TIME", "3600"),
+    ])
+    .unwrap();
+    let expected = LogConfig::new()
+        .with_level(LogLevel::Debug)
+        .with_file_prefix("Svc_".to_string())
+        .with_directory(PathBuf::from("/var/log/svc"))
+        .with_rolling_config(
+            RollingConfig::new()
+                .with_size_bytes(10 * 1024 * 1024)
+                .with_time_threshold(RollingTimeOptions::Hourly),
+        );
+    assert_eq!(config, expected);
+
+    // Numeric levels work too, and explicit builder calls win.
+    let config = with_env(&[("LOGTAR_LEVEL", "3"), ("LOGTAR_FILE_PREFIX", "Env_")]).unwrap();
+    assert_eq!(
+        config,
+        LogConfig::new()
+            .with_level(LogLevel::Error)
+            .with_file_prefix("Env_".to_string())
+    );
+    let config = config.with_file_prefix("Code_".to_string());
+    assert_eq!(
+        config,
+        LogConfig::new()
+            .with_level(LogLevel::Error)
+            .with_file_prefix("Code_".to_string())
+    );
+
+    assert_eq!(
+        error_message(&[("LOGTAR_LEVEL", "loud")]),
+        "Invalid value \"loud\" for LOGTAR_LEVEL: Unknown log level \"loud\", expected one of \
+         trace, debug, info, warn, error, critical, off or 0..=6"
+    );
+    assert_eq!(
+        error_message(&[("LOGTAR_ROLL_SIZE", "5 parsecs")]),
+        "Invalid value \"5 parsecs\" for LOGTAR_ROLL_SIZE: unknown size unit \"parsecs\""
+    );
+    assert_eq!(
+        error_message(&[("LOGTAR_ROLL_SIZE", "0")]),
+        "Invalid value \"0\" for LOGTAR_ROLL_SIZE: size must be greater than zero"
+    );
+    assert_eq!(
+        error_message(&[("LOGTAR_ROLL_TIME", "hourly")]),
+        "Invalid value \"hourly\" for LOGTAR_ROLL_TIME: Expected a number of seconds"
+    );
+    assert_eq!(
+        error_message(&[("LOGTAR_ROLL_TIME", "61")]),
+        "Invalid value \"61\" for LOGTAR_ROLL_TIME: Value does not match any known time option"
+    );
+
+    for var in VARS {
+        env::remove_var(var);
+    }
+}
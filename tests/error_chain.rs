@@ -0,0 +1,99 @@
+use std::{error::Error, fmt};
+
+use logger::{InMemoryWriter, LogConfig, LogFormat, LogLevel, Logger};
+use serde_json::{json, Value};
+
+#[derive(Debug)]
+struct Layer {
+    message: &'static str,
+    source: Option<Box<Layer>>,
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+impl Error for Layer {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|e| e as &(dyn Error + 'static))
+    }
+}
+
+// request failed <- database unavailable <- connection refused
+fn three_deep() -> Layer {
+    let root = Layer {
+        message: "connection refused",
+        source: None,
+    };
+    let mid = Layer {
+        message: "database unavailable",
+        source: Some(Box::new(root)),
+    };
+    Layer {
+        message: "request failed",
+        source: Some(Box::new(mid)),
+    }
+}
+
+fn logger(config: LogConfig) -> (Logger, InMemoryWriter) {
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    (logger, writer)
+}
+
+#[test]
+fn the_chain_is_rendered_in_order_on_one_line() {
+    let (logger, writer) = logger(LogConfig::new());
+    logger.error_chain(&three_deep());
+    logger.critical_chain(&three_deep());
+
+    let contents = writer.contents_string();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    let expected = "] error: request failed caused by: database unavailable caused by: \
+                    connection refused";
+    assert!(lines[0].contains(" ERROR ") && lines[0].ends_with(expected));
+    assert!(lines[1].contains(" CRITICAL ") && lines[1].ends_with(expected));
+}
+
+#[test]
+fn debug_renders_each_layer() {
+    let (logger, writer) = logger(LogConfig::new().with_error_debug(true));
+    let root = Layer {
+        message: "root",
+        source: None,
+    };
+    logger.error_chain(&root);
+
+    assert!(writer
+        .contents_string()
+        .ends_with("] error: root (Layer { message: \"root\", source: None })\n"));
+}
+
+#[test]
+fn json_gets_an_array() {
+    let (logger, writer) = logger(LogConfig::new().with_format(LogFormat::Json));
+    logger.log_error_chain(LogLevel::Warn, &three_deep());
+
+    let record: Value = serde_json::from_str(writer.contents_string().trim_end()).unwrap();
+    assert_eq!(record["level"], "WARN");
+    assert_eq!(
+        record["error_chain"],
+        json!([
+            "request failed",
+            "database unavailable",
+            "connection refused"
+        ])
+    );
+}
+
+#[test]
+fn filtered_chains_count_as_filtered() {
+    let (logger, writer) = logger(LogConfig::new().with_level(LogLevel::Critical));
+    logger.error_chain(&three_deep());
+
+    assert!(writer.contents().is_empty());
+    assert_eq!(logger.stats().filtered(LogLevel::Error), 1);
+}
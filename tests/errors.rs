@@ -0,0 +1,60 @@
+mod common;
+
+use std::fs;
+
+use common::file_config;
+use logger::{Logger, LoggerError};
+
+#[test]
+fn directory_that_cannot_be_created_is_an_error() {
+    let root = tempfile::tempdir().unwrap();
+    let blocker = root.path().join("not_a_dir");
+    fs::write(&blocker, "").unwrap();
+    let (config, _clock) = file_config(&blocker.join("logs"));
+
+    let error = Logger::try_new(Some(config)).unwrap_err();
+    assert!(
+        matches!(&error, LoggerError::CreateDir { path, .. } if path.ends_with("logs")),
+        "{:?}",
+        error
+    );
+    assert!(error.to_string().contains("Could not create log directory"));
+}
+
+#[cfg(unix)]
+#[test]
+fn read_only_directory_is_an_error() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o555)).unwrap();
+    // Root ignores the permission bits, so there is nothing to test.
+    if fs::write(dir.path().join("probe"), "").is_ok() {
+        return;
+    }
+    let (config, _clock) = file_config(dir.path());
+
+    let error = Logger::try_new(Some(config)).unwrap_err();
+    assert!(
+        matches!(error, LoggerError::CreateFile { .. }),
+        "{:?}",
+        error
+    );
+}
+
+#[test]
+fn a_writer_thread_that_cannot_start_is_an_error() {
+    use std::{error::Error, io};
+
+    // Spawning only fails under resource limits, so this checks how the error
+    // reads rather than provoking it.
+    let error = LoggerError::SpawnWriter(io::Error::other("Resource temporarily unavailable"));
+    assert_eq!(
+        error.to_string(),
+        "Could not spawn log writer thread: Resource temporarily unavailable"
+    );
+    assert!(error.source().is_some());
+
+    let config = logger::LogConfig::new().with_async(true);
+    assert!(Logger::try_with_writer(Some(config), Box::new(io::sink())).is_ok());
+}
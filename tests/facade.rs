@@ -0,0 +1,35 @@
+// The `log` crate logger is process-global, so everything that needs it lives
+// in this one test binary, in one test.
+mod common;
+
+use logger::{LogLevel, Logger};
+
+#[test]
+fn log_macros_reach_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = common::file_config(dir.path());
+    let logger = Logger::new(Some(config.with_level(LogLevel::Info)));
+    logger.install_global().unwrap();
+    assert_eq!(log::max_level(), log::LevelFilter::Info);
+
+    let line = line!() + 1;
+    log::info!("from the log crate");
+    log::debug!("below the minimum");
+    log::error!(target: "app::db", "with a target");
+    log::logger().flush();
+
+    let files = common::log_files(dir.path());
+    assert_eq!(files.len(), 1);
+    let contents = common::read(&files[0]);
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", contents);
+    assert!(lines[0].ends_with(&format!("INFO [{}:{}] from the log crate", file!(), line)));
+    assert!(lines[1].contains("ERROR"));
+    assert!(lines[1].ends_with("with a target"));
+
+    logger.set_level(LogLevel::Trace);
+    assert_eq!(log::max_level(), log::LevelFilter::Trace);
+
+    let other = Logger::with_writer(None, Box::new(logger::InMemoryWriter::new()));
+    assert!(other.install_global().is_err());
+}
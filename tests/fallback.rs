@@ -0,0 +1,109 @@
+use std::{
+    fs,
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use chrono::{TimeDelta, TimeZone, Utc};
+use logger::{InMemoryWriter, LogConfig, Logger, LoggerError, ManualClock, RollingConfig};
+
+// Fails every write while `broken` is set.
+struct FlakyWriter {
+    inner: InMemoryWriter,
+    broken: Arc<AtomicBool>,
+}
+
+impl Write for FlakyWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.broken.load(Ordering::SeqCst) {
+            return Err(io::Error::other("disk full"));
+        }
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn clock() -> ManualClock {
+    ManualClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap())
+}
+
+#[test]
+fn failing_writer_falls_back_and_recovers() {
+    let clock = clock();
+    let broken = Arc::new(AtomicBool::new(true));
+    let writer = InMemoryWriter::new();
+    let flaky = FlakyWriter {
+        inner: writer.clone(),
+        broken: Arc::clone(&broken),
+    };
+    let config = LogConfig::new().with_clock(Arc::new(clock.clone()));
+    let logger = Logger::with_writer(Some(config), Box::new(flaky));
+
+    logger.info("lost to stderr");
+    logger.info("also on stderr");
+    assert_eq!(logger.stats().fallback_writes(), 2);
+    assert!(matches!(logger.take_error(), Some(LoggerError::Writer(_))));
+    assert!(writer.contents().is_empty());
+
+    broken.store(false, Ordering::SeqCst);
+    clock.advance(TimeDelta::seconds(5));
+    logger.info("back");
+
+    let contents = writer.contents_string();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2, "{:?}", lines);
+    assert!(lines[0].contains("log output restored after 5s, 2 records were written to stderr"));
+    assert!(lines[1].contains("back"));
+}
+
+#[cfg(unix)]
+#[test]
+fn deleted_file_is_recreated() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = LogConfig::new().with_directory(dir.path().to_path_buf());
+    let logger = Logger::new(Some(config));
+    logger.info("first");
+    let path = logger.current_file_path().unwrap();
+    fs::remove_file(&path).unwrap();
+
+    logger.info("second");
+    assert_eq!(logger.stats().fallback_writes(), 0);
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(!contents.contains("first"));
+    assert!(contents.contains("second"));
+}
+
+#[test]
+fn failed_rotation_keeps_writing_to_the_current_file() {
+    let root = tempfile::tempdir().unwrap();
+    let dir = root.path().join("logs");
+    let config = LogConfig::new()
+        .with_directory(dir.clone())
+        .with_rolling_config(RollingConfig::new().with_size_bytes(100));
+    let logger = Logger::new(Some(config));
+    logger.info("first");
+    let name = logger.current_file_path().unwrap();
+    let name = name.file_name().unwrap();
+
+    // The open file moves along, and a plain file now blocks the directory.
+    let moved = root.path().join("moved");
+    fs::rename(&dir, &moved).unwrap();
+    fs::write(&dir, "").unwrap();
+    logger.info(&"x".repeat(200));
+    logger.info("third");
+
+    assert_eq!(logger.stats().rotation_errors(), 2);
+    assert_eq!(logger.stats().fallback_writes(), 0);
+    assert!(matches!(
+        logger.take_error(),
+        Some(LoggerError::CreateDir { .. })
+    ));
+    let contents = fs::read_to_string(moved.join(name)).unwrap();
+    assert_eq!(contents.lines().count(), 3);
+}
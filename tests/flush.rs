@@ -0,0 +1,85 @@
+mod common;
+
+use std::time::Duration;
+
+use chrono::TimeDelta;
+use common::{file_config, read};
+use logger::{FlushPolicy, Logger};
+
+fn lines_on_disk(logger: &Logger) -> usize {
+    read(&logger.current_file_path().unwrap()).lines().count()
+}
+
+#[test]
+fn errors_are_on_disk_immediately_while_info_waits_for_flush() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let logger = Logger::new(Some(
+        config.with_flush_policy(FlushPolicy::EveryRecords(100)),
+    ));
+    logger.info("buffered");
+    logger.info("also buffered");
+    assert_eq!(lines_on_disk(&logger), 0);
+
+    logger.error("urgent");
+    // The error pushes out everything before it too.
+    assert_eq!(lines_on_disk(&logger), 3);
+
+    logger.info("buffered again");
+    assert_eq!(lines_on_disk(&logger), 3);
+    logger.flush();
+    assert_eq!(lines_on_disk(&logger), 4);
+}
+
+#[test]
+fn every_n_records() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let logger = Logger::new(Some(config.with_flush_policy(FlushPolicy::EveryRecords(3))));
+    logger.info("1");
+    logger.info("2");
+    assert_eq!(lines_on_disk(&logger), 0);
+    logger.info("3");
+    assert_eq!(lines_on_disk(&logger), 3);
+    logger.info("4");
+    assert_eq!(lines_on_disk(&logger), 3);
+}
+
+#[test]
+fn interval_is_checked_on_write() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, clock) = file_config(dir.path());
+    let policy = FlushPolicy::Interval(Duration::from_millis(500));
+    let logger = Logger::new(Some(config.with_flush_policy(policy)));
+    logger.info("first");
+    clock.advance(TimeDelta::milliseconds(499));
+    logger.info("second");
+    assert_eq!(lines_on_disk(&logger), 0);
+
+    clock.advance(TimeDelta::milliseconds(1));
+    logger.info("third");
+    assert_eq!(lines_on_disk(&logger), 3);
+}
+
+#[test]
+fn every_record_is_the_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let logger = Logger::new(Some(config));
+    logger.info("unbuffered");
+    assert_eq!(lines_on_disk(&logger), 1);
+}
+
+#[test]
+fn drop_flushes() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let logger = Logger::new(Some(
+        config.with_flush_policy(FlushPolicy::EveryRecords(100)),
+    ));
+    logger.info("pending");
+    let path = logger.current_file_path().unwrap();
+    assert_eq!(read(&path), "");
+    drop(logger);
+    assert!(read(&path).ends_with("pending\n"));
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use std::sync::Arc;
+
+use chrono::TimeDelta;
+use logger::{InMemoryWriter, LogConfig, LogFormat, LogLevel, LogValue, Logger, ManualClock};
+use regex::Regex;
+use serde_json::{Map, Value};
+
+fn gelf_logger() -> (Logger, InMemoryWriter) {
+    let clock = ManualClock::new(common::start_time() + TimeDelta::milliseconds(1234));
+    let config = LogConfig::new()
+        .with_level(LogLevel::Trace)
+        .with_format(LogFormat::Gelf)
+        .with_instance_name("web-1".to_string())
+        .with_clock(Arc::new(clock));
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    (logger, writer)
+}
+
+// The GELF 1.1 payload rules: the required keys with their types, only the
+// spec's own keys without an underscore, additional field names of word
+// characters, dots and dashes, string or number values, and never `_id`.
+fn validate(line: &str) -> Map<String, Value> {
+    let Value::Object(object) = serde_json::from_str(line).unwrap() else {
+        panic!("not an object: {}", line);
+    };
+    assert_eq!(object["version"], "1.1", "{}", line);
+    assert!(
+        object["host"].as_str().is_some_and(|h| !h.is_empty()),
+        "{}",
+        line
+    );
+    assert!(
+        object["short_message"]
+            .as_str()
+            .is_some_and(|m| !m.is_empty()),
+        "{}",
+        line
+    );
+    let timestamp = object["timestamp"].as_f64().unwrap();
+    assert_eq!((timestamp * 1000.0).round() / 1000.0, timestamp, "{}", line);
+    assert!(object["level"].as_u64().is_some_and(|l| l <= 7), "{}", line);
+
+    let name = Regex::new(r"^_[\w.\-]+$").unwrap();
+    for (key, value) in &object {
+        match key.as_str() {
+            "version" | "host" | "short_message" | "full_message" | "timestamp" | "level" => {}
+            key => {
+                assert!(name.is_match(key), "bad field name {:?} in {}", key, line);
+                assert_ne!(key, "_id", "{}", line);
+                assert!(value.is_string() || value.is_number(), "{}", line);
+            }
+        }
+    }
+    object
+}
+
+fn records(writer: &InMemoryWriter) -> Vec<Map<String, Value>> {
+    writer.contents_string().lines().map(validate).collect()
+}
+
+#[test]
+fn required_fields_follow_the_spec() {
+    let (logger, writer) = gelf_logger();
+    logger.warn("disk almost full");
+
+    let record = &records(&writer)[0];
+    assert_eq!(record["host"], "web-1");
+    assert_eq!(record["short_message"], "disk almost full");
+    assert!(record.get("full_message").is_none());
+    assert_eq!(record["timestamp"], 1_704_067_201.234);
+    assert_eq!(record["level"], 4);
+}
+
+#[test]
+fn levels_use_syslog_numbering() {
+    let (logger, writer) = gelf_logger();
+    logger.trace("t");
+    logger.debug("d");
+    logger.info("i");
+    logger.warn("w");
+    logger.error("e");
+    logger.critical("c");
+
+    let levels: Vec<u64> = records(&writer)
+        .iter()
+        .map(|record| record["level"].as_u64().unwrap())
+        .collect();
+    assert_eq!(levels, [7, 7, 6, 4, 3, 2]);
+}
+
+#[test]
+fn multi_line_messages_fill_full_message() {
+    let (logger, writer) = gelf_logger();
+    logger.error("request failed\n  at handler\n  at main");
+
+    let record = &records(&writer)[0];
+    assert_eq!(record["short_message"], "request failed");
+    assert_eq!(
+        record["full_message"],
+        "request failed\n  at handler\n  at main"
+    );
+}
+
+#[test]
+fn fields_are_prefixed_and_sanitized() {
+    let (logger, writer) = gelf_logger();
+    let fields: Vec<(&str, LogValue)> = vec![
+        ("user", "alice".into()),
+        ("attempts", 3.into()),
+        ("ratio", 0.5.into()),
+        ("admin", true.into()),
+        ("http status", 200.into()),
+        ("file", "shadowed".into()),
+        ("id", "reserved".into()),
+    ];
+    logger.log_kv(LogLevel::Info, "signed in", &fields);
+
+    let record = &records(&writer)[0];
+    assert_eq!(record["_user"], "alice");
+    assert_eq!(record["_attempts"], 3);
+    assert_eq!(record["_ratio"], 0.5);
+    assert_eq!(record["_admin"], "true");
+    assert_eq!(record["_http_status"], 200);
+    assert_eq!(record["_field_file"], "shadowed");
+    assert_eq!(record["_file"], "tests/gelf.rs");
+    assert_eq!(record["_field_id"], "reserved");
+    assert!(record.get("_id").is_none());
+}
@@ -0,0 +1,86 @@
+use std::process;
+
+use logger::{InMemoryWriter, LogConfig, LogFormat, LogLevel, Logger};
+
+fn host_logger(format: LogFormat) -> (Logger, InMemoryWriter) {
+    let config = LogConfig::new()
+        .with_level(LogLevel::Trace)
+        .with_format(format)
+        .with_host_info(true)
+        .with_instance_name("api-eu-1".to_string());
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    (logger, writer)
+}
+
+fn log_everything(logger: &Logger) {
+    logger.trace("trace");
+    logger.debug("debug");
+    logger.info("info");
+    logger.warn("warn");
+    logger.error("error");
+    logger.critical("critical");
+    logger.with_fields(&[("request", 7.into())]).info("child");
+}
+
+#[test]
+fn the_instance_name_is_on_every_text_line() {
+    let (logger, writer) = host_logger(LogFormat::Text);
+    log_everything(&logger);
+
+    let contents = writer.contents_string();
+    let block = format!("[api-eu-1:{}]", process::id());
+    assert_eq!(contents.lines().count(), 7);
+    for line in contents.lines() {
+        assert_eq!(line.split(' ').nth(2), Some(block.as_str()), "{}", line);
+    }
+}
+
+#[test]
+fn json_and_logfmt_get_separate_fields() {
+    let (logger, writer) = host_logger(LogFormat::Json);
+    log_everything(&logger);
+    for line in writer.contents_string().lines() {
+        let record: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(record["host"], "api-eu-1", "{}", line);
+        assert_eq!(record["pid"], process::id(), "{}", line);
+    }
+
+    let (logger, writer) = host_logger(LogFormat::Logfmt);
+    log_everything(&logger);
+    let fields = format!(" host=api-eu-1 pid={}", process::id());
+    for line in writer.contents_string().lines() {
+        assert!(line.contains(&fields), "{}", line);
+    }
+}
+
+#[test]
+fn host_info_is_off_by_default() {
+    let config = LogConfig::new()
+        .with_format(LogFormat::Json)
+        .with_instance_name("api-eu-1".to_string());
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    logger.info("quiet");
+
+    let record: serde_json::Value =
+        serde_json::from_str(writer.contents_string().trim_end()).unwrap();
+    assert!(record.get("host").is_none());
+    assert!(record.get("pid").is_none());
+}
+
+#[test]
+fn custom_patterns_can_place_the_host() {
+    let config = LogConfig::new()
+        .with_instance_name("api-eu-1".to_string())
+        .with_pattern("%H %l %m")
+        .unwrap();
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    logger.info("placed");
+
+    assert_eq!(
+        writer.contents_string(),
+        format!("api-eu-1:{} INFO placed\n", process::id())
+    );
+}
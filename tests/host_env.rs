@@ -0,0 +1,21 @@
+use std::env;
+
+use logger::{InMemoryWriter, LogConfig, Logger};
+
+fn first_line(config: LogConfig) -> String {
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config.with_host_info(true)), Box::new(writer.clone()));
+    logger.info("hello");
+    writer.contents_string()
+}
+
+// The only test in this binary, since it sets an environment variable.
+#[test]
+fn the_instance_name_wins_over_the_environment() {
+    env::set_var("LOGTAR_HOSTNAME", "from-env");
+    assert!(first_line(LogConfig::new()).contains(" [from-env:"));
+
+    let named = first_line(LogConfig::new().with_instance_name("explicit".to_string()));
+    assert!(named.contains(" [explicit:"), "{}", named);
+    assert!(!named.contains("from-env"), "{}", named);
+}
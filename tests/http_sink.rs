@@ -0,0 +1,180 @@
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::{Bytes, Incoming},
+    server::conn::http1,
+    service::service_fn,
+    Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use logger::{ConfigError, HttpSinkConfig, InMemoryWriter, LogFormat, Logger, Sink};
+use serde_json::Value;
+
+// A collector that answers with `statuses` in turn, then 200, and keeps every
+// body it was sent.
+fn serve(statuses: Vec<u16>) -> (SocketAddr, Arc<Mutex<Vec<String>>>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let bodies = Arc::new(Mutex::new(Vec::new()));
+    let received = Arc::clone(&bodies);
+    thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async move {
+            let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let received = Arc::clone(&received);
+                let statuses = statuses.clone();
+                let service = service_fn(move |request: Request<Incoming>| {
+                    let received = Arc::clone(&received);
+                    let statuses = statuses.clone();
+                    async move {
+                        let body = request.into_body().collect().await.unwrap().to_bytes();
+                        let mut received = received.lock().unwrap();
+                        let status = statuses.get(received.len()).copied().unwrap_or(200);
+                        received.push(String::from_utf8(body.to_vec()).unwrap());
+                        let mut response = Response::new(Full::new(Bytes::new()));
+                        *response.status_mut() = StatusCode::from_u16(status).unwrap();
+                        Ok::<_, Infallible>(response)
+                    }
+                });
+                tokio::spawn(http1::Builder::new().serve_connection(TokioIo::new(stream), service));
+            }
+        });
+    });
+    (addr, bodies)
+}
+
+fn http_logger(addr: SocketAddr, batch_size: usize, format: Option<LogFormat>) -> Logger {
+    let config = HttpSinkConfig::new(&format!("http://{}/ingest", addr))
+        .unwrap()
+        .with_batch_size(batch_size)
+        .with_flush_interval(Duration::from_secs(60));
+    let mut sink = Sink::http(config);
+    if let Some(format) = format {
+        sink = sink.with_format(format);
+    }
+    let config = logger::LogConfig::new().add_sink(sink);
+    Logger::with_writer(Some(config), Box::new(InMemoryWriter::new()))
+}
+
+fn messages(body: &str) -> Vec<String> {
+    let batch: Value = serde_json::from_str(body).unwrap();
+    batch
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|record| record["message"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn wait_for(condition: impl Fn() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !condition() {
+        assert!(Instant::now() < deadline, "timed out");
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn records_are_posted_in_batches() {
+    let (addr, bodies) = serve(Vec::new());
+    let logger = http_logger(addr, 2, None);
+    for message in ["one", "two", "three"] {
+        logger.info(message);
+    }
+    // The partial batch goes out on drop.
+    drop(logger);
+
+    let bodies = bodies.lock().unwrap();
+    assert_eq!(bodies.len(), 2);
+    assert_eq!(messages(&bodies[0]), ["one", "two"]);
+    assert_eq!(messages(&bodies[1]), ["three"]);
+}
+
+#[test]
+fn server_errors_are_retried() {
+    let (addr, bodies) = serve(vec![503]);
+    let logger = http_logger(addr, 1, None);
+    logger.info("retried");
+    wait_for(|| bodies.lock().unwrap().len() == 2);
+
+    assert_eq!(logger.stats().sink_dropped(), 0);
+    let bodies = bodies.lock().unwrap();
+    assert_eq!(bodies[0], bodies[1]);
+    assert_eq!(messages(&bodies[1]), ["retried"]);
+}
+
+#[test]
+fn client_errors_drop_the_batch() {
+    let (addr, bodies) = serve(vec![400]);
+    let logger = http_logger(addr, 1, None);
+    logger.info("rejected");
+    wait_for(|| logger.stats().sink_dropped() == 1);
+    logger.info("accepted");
+    drop(logger);
+
+    let bodies = bodies.lock().unwrap();
+    assert_eq!(bodies.len(), 2);
+    assert_eq!(messages(&bodies[1]), ["accepted"]);
+}
+
+#[test]
+fn non_json_formats_still_post_json() {
+    let (addr, bodies) = serve(Vec::new());
+    let logger = http_logger(addr, 1, Some(LogFormat::Text));
+    logger.info("still json");
+    drop(logger);
+
+    assert_eq!(messages(&bodies.lock().unwrap()[0]), ["still json"]);
+}
+
+#[test]
+fn dropping_the_logger_does_not_wait_on_a_hung_collector() {
+    // Accepts connections and never answers.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let connections = Arc::new(Mutex::new(Vec::new()));
+    let held = Arc::clone(&connections);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            held.lock().unwrap().push(stream.unwrap());
+        }
+    });
+
+    let logger = http_logger(addr, 1, None);
+    logger.info("in flight");
+    wait_for(|| !connections.lock().unwrap().is_empty());
+    let started = Instant::now();
+    drop(logger);
+
+    // Well below a single request timeout, let alone the retries.
+    assert!(started.elapsed() < Duration::from_secs(7));
+}
+
+#[test]
+fn urls_are_checked() {
+    assert!(matches!(
+        HttpSinkConfig::new("ftp://collector/ingest"),
+        Err(ConfigError::InvalidUrl { .. })
+    ));
+    assert!(matches!(
+        HttpSinkConfig::new("http:///ingest"),
+        Err(ConfigError::InvalidUrl { .. })
+    ));
+    assert_eq!(
+        HttpSinkConfig::new("https://collector/ingest").is_ok(),
+        cfg!(feature = "https")
+    );
+}
@@ -0,0 +1,223 @@
+#![cfg(all(target_os = "linux", feature = "journald"))]
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    mem,
+    os::{
+        fd::{AsRawFd, FromRawFd},
+        unix::net::UnixDatagram,
+    },
+    time::Duration,
+};
+
+use logger::{InMemoryWriter, LogConfig, LogFormat, LogLevel, LogValue, Logger, Sink};
+use tempfile::TempDir;
+
+// Stands in for journald: a datagram socket the sink is pointed at.
+struct Journal {
+    _dir: TempDir,
+    socket: UnixDatagram,
+    logger: Logger,
+}
+
+fn journal(configure: impl FnOnce(Sink) -> Sink) -> Journal {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("journal.socket");
+    let socket = UnixDatagram::bind(&path).unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let config = LogConfig::new()
+        .with_level(LogLevel::Trace)
+        .add_sink(configure(Sink::journald_at(path)));
+    let logger = Logger::with_writer(Some(config), Box::new(InMemoryWriter::new()));
+    Journal {
+        _dir: dir,
+        socket,
+        logger,
+    }
+}
+
+// The native protocol: `NAME=value\n`, or `NAME\n` then a little-endian u64
+// length, the value and `\n` for values that may contain line breaks.
+fn decode(mut payload: &[u8]) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    while !payload.is_empty() {
+        let end = payload
+            .iter()
+            .position(|&b| b == b'=' || b == b'\n')
+            .expect("field name is terminated");
+        let name = String::from_utf8(payload[..end].to_vec()).unwrap();
+        let value;
+        if payload[end] == b'=' {
+            let rest = &payload[end + 1..];
+            let len = rest.iter().position(|&b| b == b'\n').unwrap();
+            value = &rest[..len];
+            payload = &rest[len + 1..];
+        } else {
+            let rest = &payload[end + 1..];
+            let len = u64::from_le_bytes(rest[..8].try_into().unwrap()) as usize;
+            value = &rest[8..8 + len];
+            assert_eq!(rest[8 + len], b'\n');
+            payload = &rest[8 + len + 1..];
+        }
+        fields.push((name, String::from_utf8(value.to_vec()).unwrap()));
+    }
+    fields
+}
+
+fn receive(socket: &UnixDatagram) -> Vec<(String, String)> {
+    let mut buf = vec![0u8; 64 * 1024];
+    let len = socket.recv(&mut buf).unwrap();
+    decode(&buf[..len])
+}
+
+fn field<'a>(fields: &'a [(String, String)], name: &str) -> &'a str {
+    &fields
+        .iter()
+        .find(|(n, _)| n == name)
+        .unwrap_or_else(|| panic!("no {} in {:?}", name, fields))
+        .1
+}
+
+#[test]
+fn records_carry_the_standard_fields() {
+    let journal = journal(|sink| sink);
+    let line = line!() + 1;
+    journal.logger.warn("disk almost full");
+
+    let fields = receive(&journal.socket);
+    assert_eq!(
+        fields,
+        [
+            ("MESSAGE".to_string(), "disk almost full".to_string()),
+            ("PRIORITY".to_string(), "4".to_string()),
+            ("CODE_FILE".to_string(), file!().to_string()),
+            ("CODE_LINE".to_string(), line.to_string()),
+        ]
+    );
+}
+
+#[test]
+fn custom_fields_are_uppercased_and_cleaned_up() {
+    let journal = journal(|sink| sink);
+    let fields: Vec<(&str, LogValue)> = vec![
+        ("user_id", 42.into()),
+        ("http.status", 200.into()),
+        ("_private", "x".into()),
+        ("1st", "y".into()),
+        ("message", "shadow".into()),
+        ("null\0byte", "a\0b".into()),
+    ];
+    journal.logger.log_kv(LogLevel::Info, "kv", &fields);
+
+    let received = receive(&journal.socket);
+    assert_eq!(field(&received, "MESSAGE"), "kv");
+    assert_eq!(field(&received, "PRIORITY"), "6");
+    assert_eq!(
+        received[4..],
+        [
+            ("USER_ID".to_string(), "42".to_string()),
+            ("HTTP_STATUS".to_string(), "200".to_string()),
+            ("PRIVATE".to_string(), "x".to_string()),
+            ("FIELD_1ST".to_string(), "y".to_string()),
+            ("FIELD_MESSAGE".to_string(), "shadow".to_string()),
+            ("NULL_BYTE".to_string(), "ab".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn multi_line_values_use_the_binary_form() {
+    let journal = journal(|sink| sink);
+    journal
+        .logger
+        .error("request failed\n  at handler\n  at main");
+
+    let mut buf = vec![0u8; 64 * 1024];
+    let len = journal.socket.recv(&mut buf).unwrap();
+    let payload = &buf[..len];
+    let message = "request failed\n  at handler\n  at main";
+    let mut expected = b"MESSAGE\n".to_vec();
+    expected.extend_from_slice(&(message.len() as u64).to_le_bytes());
+    expected.extend_from_slice(message.as_bytes());
+    expected.push(b'\n');
+    assert!(payload.starts_with(&expected));
+    assert_eq!(field(&decode(payload), "MESSAGE"), message);
+}
+
+#[test]
+fn other_formats_become_the_message() {
+    let journal = journal(|sink| sink.with_format(LogFormat::Json));
+    journal.logger.critical("as json");
+
+    let fields = receive(&journal.socket);
+    assert_eq!(fields.len(), 2);
+    let record: serde_json::Value = serde_json::from_str(field(&fields, "MESSAGE")).unwrap();
+    assert_eq!(record["message"], "as json");
+    assert_eq!(field(&fields, "PRIORITY"), "2");
+}
+
+// Takes the one descriptor a datagram with no data passes over SCM_RIGHTS.
+fn receive_fd(socket: &UnixDatagram) -> File {
+    // SAFETY: CMSG_SPACE only computes a size.
+    let space = unsafe { libc::CMSG_SPACE(mem::size_of::<libc::c_int>() as u32) } as usize;
+    let mut control = vec![0u64; space.div_ceil(mem::size_of::<u64>())];
+    let mut data = [0u8; 1];
+    let mut iov = libc::iovec {
+        iov_base: data.as_mut_ptr().cast(),
+        iov_len: data.len(),
+    };
+    // SAFETY: msghdr is plain data, all zeroes is a valid value.
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr().cast();
+    msg.msg_controllen = space as _;
+    // SAFETY: every pointer in msg stays valid for the call.
+    let received = unsafe { libc::recvmsg(socket.as_raw_fd(), &mut msg, 0) };
+    assert!(received >= 0, "{}", std::io::Error::last_os_error());
+    // SAFETY: recvmsg filled in the control buffer msg points at.
+    unsafe {
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        assert!(!cmsg.is_null(), "no descriptor was passed");
+        assert_eq!((*cmsg).cmsg_type, libc::SCM_RIGHTS);
+        let fd = std::ptr::read_unaligned(libc::CMSG_DATA(cmsg).cast::<libc::c_int>());
+        File::from_raw_fd(fd)
+    }
+}
+
+#[test]
+fn entries_too_big_for_a_datagram_go_through_a_sealed_memfd() {
+    let journal = journal(|sink| sink);
+    let message = "x".repeat(4 * 1024 * 1024);
+    journal.logger.info(&message);
+
+    let mut memfd = receive_fd(&journal.socket);
+    // SAFETY: F_GET_SEALS takes no argument besides the descriptor.
+    let seals = unsafe { libc::fcntl(memfd.as_raw_fd(), libc::F_GET_SEALS) };
+    let expected = libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE | libc::F_SEAL_SEAL;
+    assert_eq!(seals & expected, expected);
+
+    let mut payload = Vec::new();
+    memfd.seek(SeekFrom::Start(0)).unwrap();
+    memfd.read_to_end(&mut payload).unwrap();
+    let fields = decode(&payload);
+    assert_eq!(field(&fields, "MESSAGE"), message);
+    assert_eq!(field(&fields, "PRIORITY"), "6");
+    assert_eq!(journal.logger.stats().write_errors(), 0);
+}
+
+#[test]
+fn a_missing_socket_drops_records_quietly() {
+    let dir = tempfile::tempdir().unwrap();
+    let main = InMemoryWriter::new();
+    let config = LogConfig::new().add_sink(Sink::journald_at(dir.path().join("absent.socket")));
+    let logger = Logger::with_writer(Some(config), Box::new(main.clone()));
+    logger.info("one");
+    logger.error("two");
+
+    assert_eq!(logger.stats().write_errors(), 0);
+    assert_eq!(main.contents_string().lines().count(), 2);
+}
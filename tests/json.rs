@@ -0,0 +1,40 @@
+mod common;
+
+use logger::{LogFormat, LogLevel, Logger};
+use serde_json::{json, Value};
+
+#[test]
+fn every_line_parses_back() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = common::file_config(dir.path());
+    let logger = Logger::new(Some(config.with_format(LogFormat::Json)));
+    let line = line!() + 1;
+    logger.info("plain");
+    logger.warn("with \"quotes\"\nand a second line");
+    logger.log_kv(LogLevel::Error, "failed", &[("code", 7.into())]);
+    logger.flush();
+
+    let files = common::log_files(dir.path());
+    assert_eq!(files.len(), 1);
+    let contents = common::read(&files[0]);
+    let records: Vec<Value> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 3);
+
+    let expected = [
+        ("INFO", "plain"),
+        ("WARN", "with \"quotes\"\nand a second line"),
+        ("ERROR", "failed"),
+    ];
+    for (i, (record, (level, message))) in records.iter().zip(expected).enumerate() {
+        assert_eq!(record["timestamp"], "2024-01-01T00:00:00.000Z");
+        assert_eq!(record["level"], level);
+        assert_eq!(record["message"], message);
+        assert_eq!(record["file"], file!());
+        assert_eq!(record["line"], line + i as u32);
+    }
+    assert!(records[0].get("fields").is_none());
+    assert_eq!(records[2]["fields"], json!({ "code": 7 }));
+}
@@ -0,0 +1,106 @@
+use logger::{InMemoryWriter, LogLevel, LogValue, Logger};
+
+fn logger() -> (Logger, InMemoryWriter) {
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(None, Box::new(writer.clone()));
+    (logger, writer)
+}
+
+// Everything after the location, so the tests don't depend on line numbers.
+fn tails(writer: &InMemoryWriter) -> Vec<String> {
+    writer
+        .contents_string()
+        .lines()
+        .map(|line| line.split_once("] ").unwrap().1.to_string())
+        .collect()
+}
+
+#[test]
+fn values_are_quoted_and_escaped_when_needed() {
+    let (logger, writer) = logger();
+    logger.log_kv(
+        LogLevel::Info,
+        "login",
+        &[
+            ("user", "alice".into()),
+            ("name", "Alice Smith".into()),
+            ("quote", "say \"hi\"".into()),
+            ("path", "C:\\tmp".into()),
+            ("eq", "a=b".into()),
+            ("lines", "one\ntwo\r\tend".into()),
+            ("empty", "".into()),
+        ],
+    );
+
+    assert_eq!(
+        tails(&writer),
+        [concat!(
+            r#"login user=alice name="Alice Smith" quote="say \"hi\"" path="C:\\tmp" "#,
+            r#"eq="a=b" lines="one\ntwo\r\tend" empty="""#
+        )]
+    );
+}
+
+#[test]
+fn numbers_and_bools_are_bare() {
+    let (logger, writer) = logger();
+    logger.log_kv(
+        LogLevel::Info,
+        "values",
+        &[
+            ("int", (-42).into()),
+            ("uint", 7u64.into()),
+            ("float", 1.5.into()),
+            ("flag", true.into()),
+        ],
+    );
+
+    assert_eq!(
+        tails(&writer),
+        ["values int=-42 uint=7 float=1.5 flag=true"]
+    );
+}
+
+#[test]
+fn empty_field_lists_add_nothing() {
+    let (logger, writer) = logger();
+    logger.log_kv(LogLevel::Info, "plain", &[]);
+    logger.with_fields(&[]).info("bound");
+
+    assert_eq!(tails(&writer), ["plain", "bound"]);
+    assert!(writer
+        .contents_string()
+        .lines()
+        .all(|line| !line.ends_with(' ')));
+}
+
+#[test]
+fn fields_keep_their_order() {
+    let (logger, writer) = logger();
+    let fields: Vec<(&str, LogValue)> = ["z", "a", "m", "b"]
+        .iter()
+        .enumerate()
+        .map(|(i, key)| (*key, i.into()))
+        .collect();
+    for _ in 0..3 {
+        logger.log_kv(LogLevel::Info, "ordered", &fields);
+    }
+
+    assert_eq!(tails(&writer), ["ordered z=0 a=1 m=2 b=3"; 3]);
+}
+
+#[test]
+fn bound_fields_come_first_and_call_site_fields_override() {
+    let (logger, writer) = logger();
+    let child = logger.with_fields(&[("service", "api".into()), ("request_id", 1.into())]);
+    child.log_kv(
+        LogLevel::Info,
+        "handled",
+        &[("status", 200.into()), ("request_id", 2.into())],
+    );
+
+    assert_eq!(
+        tails(&writer),
+        ["handled service=api status=200 request_id=2"]
+    );
+}
@@ -0,0 +1,60 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+
+use common::{file_config, log_files, read};
+use logger::{Logger, RollingConfig};
+
+#[test]
+fn the_link_follows_rotation() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let config = config
+        .with_latest_symlink(true)
+        .with_rolling_config(RollingConfig::new().with_size_bytes(100));
+    let logger = Logger::new(Some(config));
+    let link = dir.path().join("App_latest.log");
+    assert_eq!(
+        fs::canonicalize(&link).unwrap(),
+        fs::canonicalize(logger.current_file_path().unwrap()).unwrap()
+    );
+
+    logger.info(&"x".repeat(80));
+    logger.info("forces a rotation");
+    let newest = logger.current_file_path().unwrap();
+    drop(logger);
+
+    assert_eq!(log_files(dir.path()).len(), 2);
+    assert!(fs::symlink_metadata(&link)
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    // Relative, so the directory can be moved.
+    assert_eq!(fs::read_link(&link).unwrap(), newest.file_name().unwrap());
+    assert!(read(&link).ends_with("forces a rotation\n"));
+}
+
+#[test]
+fn no_link_unless_asked() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    Logger::new(Some(config)).info("no link");
+
+    assert!(fs::symlink_metadata(dir.path().join("App_latest.log")).is_err());
+}
+
+#[test]
+fn a_broken_link_location_doesnt_stop_logging() {
+    let dir = tempfile::tempdir().unwrap();
+    // A directory where the link should go can't be renamed over.
+    fs::create_dir_all(dir.path().join("App_latest.log/blocker")).unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let logger = Logger::new(Some(config.with_latest_symlink(true)));
+    logger.info("still logged");
+    let path = logger.current_file_path().unwrap();
+    drop(logger);
+
+    assert!(read(&path).ends_with("still logged\n"));
+}
@@ -0,0 +1,72 @@
+use std::{cell::Cell, sync::Arc};
+
+use chrono::{TimeZone, Utc};
+use logger::{InMemoryWriter, LogConfig, LogLevel, Logger, ManualClock};
+
+fn logger_with(config: LogConfig) -> (Logger, InMemoryWriter) {
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    (logger, writer)
+}
+
+#[test]
+fn disabled_level_never_calls_the_closure() {
+    let (logger, writer) = logger_with(LogConfig::new().with_level(LogLevel::Warn));
+    logger.debug_with(|| panic!("built a filtered message"));
+    logger.trace_with(|| unreachable!());
+
+    assert!(!logger.enabled(LogLevel::Debug, None));
+    assert!(writer.contents().is_empty());
+    assert_eq!(logger.stats().filtered(LogLevel::Debug), 1);
+}
+
+#[test]
+fn enabled_level_calls_the_closure_once() {
+    let (logger, writer) = logger_with(LogConfig::new());
+    let calls = Cell::new(0);
+    logger.info_with(|| {
+        calls.set(calls.get() + 1);
+        "built".to_string()
+    });
+
+    assert_eq!(calls.get(), 1);
+    assert!(writer.contents_string().contains("built"));
+}
+
+#[test]
+fn target_levels_apply_to_lazy_messages() {
+    let config = LogConfig::new()
+        .with_level(LogLevel::Warn)
+        .with_target_level("app::db", LogLevel::Debug);
+    let (logger, writer) = logger_with(config);
+    logger.log_target_with(LogLevel::Debug, "app::db::pool", || "pool".to_string());
+    logger.log_target_with(LogLevel::Debug, "app::http", || panic!("filtered"));
+
+    assert!(logger.enabled(LogLevel::Debug, Some("app::db")));
+    assert!(!logger.enabled(LogLevel::Debug, Some("app::http")));
+    let contents = writer.contents_string();
+    assert_eq!(contents.lines().count(), 1);
+    assert!(contents.contains("pool"));
+}
+
+#[test]
+fn throttled_call_site_skips_the_closure() {
+    let clock = ManualClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+    let config = LogConfig::new()
+        .with_rate_limit(1)
+        .with_clock(Arc::new(clock));
+    let (logger, writer) = logger_with(config);
+    let calls = Cell::new(0);
+    for _ in 0..3 {
+        logger.warn_with(|| {
+            calls.set(calls.get() + 1);
+            "busy".to_string()
+        });
+    }
+    drop(logger);
+
+    assert_eq!(calls.get(), 1);
+    assert!(writer
+        .contents_string()
+        .contains("suppressed 2 similar messages"));
+}
@@ -0,0 +1,150 @@
+use std::fs;
+
+use logger::{ConfigError, InMemoryWriter, LogConfig, LogLevel, Logger};
+
+const ALL: [LogLevel; 7] = [
+    LogLevel::Trace,
+    LogLevel::Debug,
+    LogLevel::Info,
+    LogLevel::Warn,
+    LogLevel::Error,
+    LogLevel::Critical,
+    LogLevel::Off,
+];
+
+#[test]
+fn names_parse_in_any_case() {
+    let cases = [
+        ("trace", LogLevel::Trace),
+        ("DEBUG", LogLevel::Debug),
+        ("Info", LogLevel::Info),
+        ("wArN", LogLevel::Warn),
+        ("error", LogLevel::Error),
+        ("CRITICAL", LogLevel::Critical),
+        ("off", LogLevel::Off),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(input.parse::<LogLevel>(), Ok(expected), "{}", input);
+    }
+}
+
+#[test]
+fn numbers_keep_their_historic_meaning() {
+    let cases = [
+        (0, LogLevel::Debug),
+        (1, LogLevel::Info),
+        (2, LogLevel::Warn),
+        (3, LogLevel::Error),
+        (4, LogLevel::Critical),
+        (5, LogLevel::Trace),
+        (6, LogLevel::Off),
+    ];
+    for (number, expected) in cases {
+        assert_eq!(LogLevel::try_from(number), Ok(expected), "{}", number);
+        assert_eq!(number.to_string().parse::<LogLevel>(), Ok(expected));
+    }
+}
+
+#[test]
+fn display_round_trips() {
+    for level in ALL {
+        assert_eq!(level.to_string().parse::<LogLevel>(), Ok(level));
+    }
+}
+
+#[test]
+fn invalid_inputs_list_the_valid_values() {
+    for input in ["", "verbose", "warning", "7", "-1", " info", "info "] {
+        let error = input.parse::<LogLevel>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Unknown log level {:?}, expected one of trace, debug, info, warn, error, \
+                 critical, off or 0..=6",
+                input
+            )
+        );
+    }
+    assert!(LogLevel::try_from(7).is_err());
+    assert!(LogLevel::try_from(u64::MAX).is_err());
+}
+
+#[test]
+fn json_configs_take_names_and_numbers() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    for (level, expected) in [
+        (r#""warn""#, LogLevel::Warn),
+        (r#""Critical""#, LogLevel::Critical),
+        ("2", LogLevel::Warn),
+        ("5", LogLevel::Trace),
+    ] {
+        fs::write(&path, format!(r#"{{ "level": {} }}"#, level)).unwrap();
+        let config = LogConfig::from_json_file(&path).unwrap();
+        assert_eq!(config, LogConfig::new().with_level(expected), "{}", level);
+    }
+
+    fs::write(&path, r#"{ "level": "loud" }"#).unwrap();
+    match LogConfig::from_json_file(&path) {
+        Err(e @ ConfigError::OutOfRange { .. }) => assert_eq!(
+            e.to_string(),
+            "Invalid value for level: Unknown log level \"loud\", expected one of trace, debug, \
+             info, warn, error, critical, off or 0..=6"
+        ),
+        other => panic!("expected OutOfRange, got {:?}", other),
+    }
+}
+
+#[test]
+fn levels_are_ordered_by_severity() {
+    for pair in ALL.windows(2) {
+        assert!(pair[0] < pair[1], "{:?} < {:?}", pair[0], pair[1]);
+    }
+    assert!(LogLevel::Trace < LogLevel::Debug);
+    assert!(LogLevel::Off > LogLevel::Critical);
+    assert_eq!(ALL.iter().max(), Some(&LogLevel::Off));
+}
+
+fn logger_at(level: LogLevel) -> (Logger, InMemoryWriter) {
+    let writer = InMemoryWriter::new();
+    let config = LogConfig::new().with_level(level);
+    (
+        Logger::with_writer(Some(config), Box::new(writer.clone())),
+        writer,
+    )
+}
+
+#[test]
+fn off_suppresses_critical() {
+    let (logger, writer) = logger_at(LogLevel::Off);
+    logger.trace("trace");
+    logger.info("info");
+    logger.critical("critical");
+    logger::critical!(logger, "macro {}", 1);
+    assert!(!logger.enabled(LogLevel::Critical, None));
+    assert_eq!(writer.contents_string(), "");
+}
+
+#[test]
+fn off_is_never_a_record_level() {
+    let (logger, writer) = logger_at(LogLevel::Trace);
+    logger.log(LogLevel::Off, "not a severity");
+    assert!(!logger.enabled(LogLevel::Off, None));
+    assert_eq!(writer.contents_string(), "");
+}
+
+#[test]
+fn trace_is_written_only_at_trace() {
+    let (logger, writer) = logger_at(LogLevel::Trace);
+    logger.trace("dump");
+    logger::trace!(logger, "frame {}", 7);
+    let contents = writer.contents_string();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains(" TRACE [") && lines[0].ends_with("] dump"));
+    assert!(lines[1].ends_with("] frame 7"));
+
+    let (logger, writer) = logger_at(LogLevel::Debug);
+    logger.trace("dump");
+    assert_eq!(writer.contents_string(), "");
+}
@@ -0,0 +1,68 @@
+use std::fmt;
+
+use logger::{info, warn_limited, InMemoryWriter, LogConfig, LogLevel, Logger};
+
+// Runs the logging call and returns the line it was on.
+macro_rules! logged_at {
+    ($call:expr) => {{
+        $call;
+        line!()
+    }};
+}
+
+#[derive(Debug)]
+struct Failure;
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("failure")
+    }
+}
+
+impl std::error::Error for Failure {}
+
+#[test]
+fn every_entry_point_reports_its_call_site() {
+    let writer = InMemoryWriter::new();
+    let config = LogConfig::new().with_level(LogLevel::Trace);
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    let lines = [
+        logged_at!(logger.info("plain")),
+        logged_at!(logger.log(LogLevel::Warn, "log")),
+        logged_at!(logger.log_kv(LogLevel::Info, "kv", &[("key", 1.into())])),
+        logged_at!(logger.log_target(LogLevel::Info, "app", "target", &[])),
+        logged_at!(logger.debug_with(|| "lazy".to_string())),
+        logged_at!(logger.error_chain(&Failure)),
+        logged_at!(info!(logger, "macro {}", 1)),
+        logged_at!(warn_limited!(logger, 5 / sec, "limited")),
+    ];
+
+    let contents = writer.contents_string();
+    let reported: Vec<&str> = contents.lines().collect();
+    assert_eq!(reported.len(), lines.len());
+    for (line, expected) in reported.iter().zip(lines) {
+        let location = format!("[{}:{}]", file!(), expected);
+        assert!(
+            line.contains(&location),
+            "{} doesn't contain {}",
+            line,
+            location
+        );
+    }
+}
+
+#[track_caller]
+fn helper(logger: &Logger) {
+    logger.info("from a helper");
+}
+
+#[test]
+fn track_caller_helpers_report_their_caller() {
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(None, Box::new(writer.clone()));
+    let line = logged_at!(helper(&logger));
+
+    assert!(writer
+        .contents_string()
+        .contains(&format!("[{}:{}]", file!(), line)));
+}
@@ -0,0 +1,148 @@
+// Windows has no advisory locks, so log files aren't locked there.
+#![cfg(unix)]
+
+mod common;
+
+use std::{fs, thread};
+
+use common::{file_config, log_files, read};
+use logger::{LockConflict, Logger, LoggerError, RollingConfig};
+
+// Each file must hold records from one logger only.
+fn assert_not_interleaved(dir: &std::path::Path) {
+    for path in log_files(dir) {
+        let contents = read(&path);
+        let owners: Vec<&str> = contents
+            .lines()
+            .map(|line| line.rsplit_once("] ").unwrap().1.split(':').next().unwrap())
+            .collect();
+        assert!(
+            owners.windows(2).all(|pair| pair[0] == pair[1]),
+            "{} mixes loggers:\n{}",
+            path.display(),
+            contents
+        );
+    }
+}
+
+fn write_from_both(first: &Logger, second: &Logger, records: usize) {
+    thread::scope(|scope| {
+        for (name, logger) in [("first", first), ("second", second)] {
+            scope.spawn(move || {
+                for i in 0..records {
+                    logger.info(&format!("{}:{}", name, i));
+                }
+            });
+        }
+    });
+}
+
+#[test]
+fn a_second_logger_writes_its_own_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    // Same directory, prefix and clock time: the same file name for both.
+    let first = Logger::new(Some(config.clone()));
+    let second = Logger::new(Some(config.with_reuse_latest(true)));
+    write_from_both(&first, &second, 500);
+
+    let files = log_files(dir.path());
+    assert_eq!(files.len(), 2);
+    assert_ne!(first.current_file_path(), second.current_file_path());
+    assert!(common::file_name(&files[1]).ends_with(".1.log"));
+    for path in &files {
+        assert_eq!(read(path).lines().count(), 500);
+    }
+    assert_not_interleaved(dir.path());
+}
+
+#[test]
+fn rotating_side_by_side_never_shares_or_deletes_a_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let config =
+        config.with_rolling_config(RollingConfig::new().with_size_bytes(2000).with_max_files(3));
+    let first = Logger::new(Some(config.clone()));
+    let second = Logger::new(Some(config));
+    write_from_both(&first, &second, 1000);
+
+    assert!(log_files(dir.path()).len() > 2);
+    assert_not_interleaved(dir.path());
+    // Neither logger's retention removed the other's active file.
+    assert!(first.current_file_path().unwrap().exists());
+    assert!(second.current_file_path().unwrap().exists());
+}
+
+#[test]
+fn error_mode_refuses_a_locked_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let config = config.with_lock_conflict(LockConflict::Error);
+    let first = Logger::try_new(Some(config.clone())).unwrap();
+
+    match Logger::try_new(Some(config.clone())) {
+        Err(LoggerError::Locked { path }) => {
+            assert_eq!(Some(path), first.current_file_path());
+        }
+        other => panic!("expected Locked, got {:?}", other.err()),
+    }
+
+    // Dropping the first logger releases its lock.
+    drop(first);
+    assert!(Logger::try_new(Some(config)).is_ok());
+}
+
+#[test]
+fn a_reopened_file_stays_locked() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let first = Logger::new(Some(config.clone()));
+    first.info("first:before");
+    first.reopen().unwrap();
+
+    let error_mode = config.clone().with_lock_conflict(LockConflict::Error);
+    assert!(matches!(
+        Logger::try_new(Some(error_mode)),
+        Err(LoggerError::Locked { .. })
+    ));
+    let second = Logger::new(Some(config.with_reuse_latest(true)));
+    assert_ne!(first.current_file_path(), second.current_file_path());
+    write_from_both(&first, &second, 100);
+    assert_not_interleaved(dir.path());
+}
+
+#[test]
+fn reopening_a_path_another_logger_took_starts_a_new_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let first = Logger::new(Some(config.clone()));
+    first.info("first:before");
+    let path = first.current_file_path().unwrap();
+    fs::rename(&path, dir.path().join("moved.txt")).unwrap();
+    // The same clock time gives the second logger the name the first one had.
+    let second = Logger::new(Some(config));
+    assert_eq!(second.current_file_path(), Some(path.clone()));
+
+    first.reopen().unwrap();
+    assert_ne!(first.current_file_path(), Some(path));
+    write_from_both(&first, &second, 100);
+    assert_not_interleaved(dir.path());
+}
+
+#[test]
+fn a_panic_releases_the_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let config = config.with_lock_conflict(LockConflict::Error);
+
+    let doomed = config.clone();
+    let result = thread::spawn(move || {
+        let logger = Logger::try_new(Some(doomed)).unwrap();
+        logger.info("about to panic");
+        panic!("boom");
+    })
+    .join();
+    assert!(result.is_err());
+
+    assert!(Logger::try_new(Some(config)).is_ok());
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use std::sync::Arc;
+
+use logger::{InMemoryWriter, LogConfig, LogFormat, LogLevel, LogValue, Logger, ManualClock};
+
+// Just enough logfmt: space-separated key=value pairs, values optionally quoted
+// with backslash escapes.
+fn parse(line: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.peek() == Some(&' ') {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            return pairs;
+        }
+        let key: String = chars.by_ref().take_while(|&c| c != '=').collect();
+        let mut value = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => match chars.next() {
+                        Some('n') => value.push('\n'),
+                        Some('r') => value.push('\r'),
+                        Some('t') => value.push('\t'),
+                        Some(c) => value.push(c),
+                        None => panic!("dangling escape in {:?}", line),
+                    },
+                    c => value.push(c),
+                }
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c == ' ' {
+                    break;
+                }
+                assert!(c != '"' && c != '=', "unquoted {:?} in {:?}", c, line);
+                value.push(c);
+                chars.next();
+            }
+        }
+        pairs.push((key, value));
+    }
+}
+
+fn logfmt_logger() -> (Logger, InMemoryWriter) {
+    let config = LogConfig::new()
+        .with_format(LogFormat::Logfmt)
+        .with_clock(Arc::new(ManualClock::new(common::start_time())));
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    (logger, writer)
+}
+
+fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+    expected
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn built_in_keys_come_first() {
+    let (logger, writer) = logfmt_logger();
+    let line = line!() + 1;
+    logger.info("hello world");
+
+    let output = writer.contents_string();
+    assert_eq!(
+        output,
+        format!(
+            "ts=2024-01-01T00:00:00.000Z level=info msg=\"hello world\" file={} line={}\n",
+            file!(),
+            line
+        )
+    );
+    assert_eq!(
+        parse(output.trim_end()),
+        pairs(&[
+            ("ts", "2024-01-01T00:00:00.000Z"),
+            ("level", "info"),
+            ("msg", "hello world"),
+            ("file", file!()),
+            ("line", &line.to_string()),
+        ])
+    );
+}
+
+#[test]
+fn awkward_values_round_trip() {
+    let values = [
+        "plain",
+        "with space",
+        "say \"hi\"",
+        "a=b",
+        "back\\slash",
+        "line\nbreak\r\ttab",
+        "",
+        "ünïcødé",
+    ];
+    let (logger, writer) = logfmt_logger();
+    for value in values {
+        logger.log_kv(LogLevel::Warn, value, &[("value", value.into())]);
+    }
+
+    let output = writer.contents_string();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), values.len());
+    for (line, value) in lines.iter().zip(values) {
+        let parsed = parse(line);
+        assert_eq!(parsed[2], ("msg".to_string(), value.to_string()));
+        assert_eq!(parsed[5], ("value".to_string(), value.to_string()));
+    }
+}
+
+#[test]
+fn reserved_field_names_are_prefixed() {
+    let (logger, writer) = logfmt_logger();
+    let fields: Vec<(&str, LogValue)> = vec![
+        ("level", "custom".into()),
+        ("msg", "shadow".into()),
+        ("user_id", 42.into()),
+        ("ts", true.into()),
+    ];
+    logger.log_kv(LogLevel::Error, "collide", &fields);
+
+    let parsed = parse(writer.contents_string().trim_end());
+    assert_eq!(
+        parsed[5..],
+        pairs(&[
+            ("field_level", "custom"),
+            ("field_msg", "shadow"),
+            ("user_id", "42"),
+            ("field_ts", "true"),
+        ])
+    );
+    assert_eq!(parsed[1], ("level".to_string(), "error".to_string()));
+    assert_eq!(parsed[2], ("msg".to_string(), "collide".to_string()));
+}
@@ -0,0 +1,60 @@
+use std::{cell::Cell, fmt};
+
+use logger::{debug, error, info, trace, warn, InMemoryWriter, LogConfig, LogLevel, Logger};
+
+// Panics if it is ever formatted.
+struct Explodes;
+
+impl fmt::Display for Explodes {
+    fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+        panic!("formatted a filtered record")
+    }
+}
+
+fn logger_at(level: LogLevel) -> (Logger, InMemoryWriter) {
+    let writer = InMemoryWriter::new();
+    let config = LogConfig::new()
+        .with_level(level)
+        .with_target_level("app::db", LogLevel::Trace);
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    (logger, writer)
+}
+
+#[test]
+fn filtered_records_are_never_formatted() {
+    let (logger, writer) = logger_at(LogLevel::Warn);
+    debug!(logger, "{}", Explodes);
+    trace!(logger, "{}", Explodes);
+    info!(target: "app::http", logger, "{}", Explodes);
+
+    assert!(writer.contents().is_empty());
+    assert_eq!(logger.stats().filtered(LogLevel::Debug), 1);
+    assert_eq!(logger.stats().filtered(LogLevel::Info), 1);
+}
+
+#[test]
+fn arguments_are_evaluated_once_when_enabled() {
+    let (logger, writer) = logger_at(LogLevel::Info);
+    let calls = Cell::new(0);
+    let next = || {
+        calls.set(calls.get() + 1);
+        calls.get()
+    };
+    warn!(logger, "call {}", next());
+    error!(logger, "inline {value}", value = "args");
+
+    assert_eq!(calls.get(), 1);
+    let contents = writer.contents_string();
+    assert!(contents.contains("WARN"));
+    assert!(contents.contains("call 1"));
+    assert!(contents.contains("inline args"));
+}
+
+#[test]
+fn target_levels_apply_to_macros() {
+    let (logger, writer) = logger_at(LogLevel::Warn);
+    trace!(target: "app::db::pool", logger, "pool");
+    trace!(logger, "{}", Explodes);
+
+    assert_eq!(writer.contents_string().lines().count(), 1);
+}
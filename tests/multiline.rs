@@ -0,0 +1,55 @@
+mod common;
+
+use common::{file_config, read};
+use logger::{LogFormat, LogLevel, Logger, Multiline};
+
+const THREE_LINES: &str = "request failed\n  at handler\n  at main";
+
+fn logged(multiline: Multiline, format: LogFormat) -> String {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let logger = Logger::new(Some(config.with_multiline(multiline).with_format(format)));
+    logger.error(THREE_LINES);
+    logger.log_kv(LogLevel::Info, "after", &[("body", "a\nb".into())]);
+    read(&logger.current_file_path().unwrap())
+}
+
+#[test]
+fn raw_keeps_the_line_breaks() {
+    let contents = logged(Multiline::Raw, LogFormat::Text);
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].ends_with("] request failed"));
+    assert_eq!(lines[1..3], ["  at handler", "  at main"]);
+}
+
+#[test]
+fn escape_puts_each_record_on_one_line() {
+    let contents = logged(Multiline::Escape, LogFormat::Text);
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("] request failed\\n  at handler\\n  at main"));
+    assert!(lines[1].ends_with("] after body=\"a\\nb\""));
+}
+
+#[test]
+fn indent_marks_continuation_lines_with_a_tab() {
+    let contents = logged(Multiline::Indent, LogFormat::Text);
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[1..3], ["\t  at handler", "\t  at main"]);
+    // Lines that don't start with a tab begin a new record.
+    let records = lines.iter().filter(|line| !line.starts_with('\t')).count();
+    assert_eq!(records, 2);
+}
+
+#[test]
+fn json_is_one_line_per_record_in_every_mode() {
+    for multiline in [Multiline::Raw, Multiline::Escape, Multiline::Indent] {
+        let contents = logged(multiline, LogFormat::Json);
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2, "{:?}", multiline);
+        let record: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(record["message"], THREE_LINES);
+    }
+}
@@ -0,0 +1,64 @@
+// Panic hooks are process-global, so everything that installs one lives in this
+// one test binary, in one test.
+mod common;
+
+use std::{
+    io::{self, Write},
+    thread,
+};
+
+use logger::Logger;
+
+// Panics while the logger holds its lock for the write.
+struct Exploding;
+
+impl Write for Exploding {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        panic!("writer exploded")
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn panics_are_logged_as_critical() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = common::file_config(dir.path());
+    let logger = Logger::new(Some(config));
+    logger.install_panic_hook();
+    let path = logger.current_file_path().unwrap();
+
+    let line = line!() + 4;
+    let result = thread::Builder::new()
+        .name("doomed".to_string())
+        .spawn(|| {
+            panic!("task failed: {}", 42);
+        })
+        .unwrap()
+        .join();
+    assert!(result.is_err());
+
+    let contents = common::read(&path);
+    let first = contents.lines().next().unwrap();
+    assert_eq!(
+        first,
+        format!(
+            "2024-01-01T00:00:00.000Z CRITICAL [{}:{}] thread 'doomed' panicked: task failed: 42",
+            file!(),
+            line
+        )
+    );
+    // The backtrace follows on the next lines.
+    assert!(contents.lines().count() > 1);
+    assert!(contents.contains("panic_hook"), "{}", contents);
+
+    // A panic inside the logger's own write can't take its lock again; it goes
+    // to stderr, and the earlier hook still runs.
+    let inner = Logger::with_writer(None, Box::new(Exploding));
+    inner.install_panic_hook();
+    let result = thread::spawn(move || inner.info("boom")).join();
+    assert!(result.is_err());
+    assert!(common::read(&path).contains("panicked: writer exploded"));
+}
@@ -0,0 +1,69 @@
+use std::{sync::Arc, thread};
+
+use chrono::{TimeZone, Utc};
+use logger::{ConfigError, InMemoryWriter, LogConfig, LogLevel, Logger, ManualClock};
+
+// Logs the same record under `pattern` from a thread named "worker". `{line}`
+// in the output stands for the line of the call.
+fn render(pattern: &str) -> String {
+    let clock = ManualClock::new(Utc.with_ymd_and_hms(2024, 5, 1, 10, 0, 0).unwrap());
+    let config = LogConfig::new()
+        .with_clock(Arc::new(clock))
+        .with_pattern(pattern)
+        .unwrap();
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    let line = thread::Builder::new()
+        .name("worker".to_string())
+        .spawn(move || {
+            let line = line!() + 1;
+            logger.log_kv(LogLevel::Warn, "disk almost full", &[("free", 3.into())]);
+            line
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    writer
+        .contents_string()
+        .replace(&format!(":{}]", line), ":{line}]")
+}
+
+#[test]
+fn patterns_render_exactly() {
+    let cases = [
+        (
+            "%t %l [%f:%L] %m",
+            "2024-05-01T10:00:00.000Z WARN [tests/pattern.rs:{line}] disk almost full free=3\n",
+        ),
+        ("%l: %m", "WARN: disk almost full free=3\n"),
+        (
+            "[%T] %m (%f)",
+            "[worker] disk almost full (tests/pattern.rs) free=3\n",
+        ),
+        ("%m", "disk almost full free=3\n"),
+        ("100%% %l", "100% WARN free=3\n"),
+        ("", " free=3\n"),
+    ];
+    for (pattern, expected) in cases {
+        assert_eq!(render(pattern), expected, "{:?}", pattern);
+    }
+}
+
+#[test]
+fn invalid_placeholders_are_config_errors() {
+    for (pattern, reason) in [
+        ("%l %q", "unknown placeholder %q"),
+        ("trailing %", "pattern ends with a lone %"),
+    ] {
+        match LogConfig::new().with_pattern(pattern) {
+            Err(ConfigError::InvalidPattern {
+                pattern: reported,
+                reason: reported_reason,
+            }) => {
+                assert_eq!(reported, pattern);
+                assert_eq!(reported_reason, reason);
+            }
+            other => panic!("expected InvalidPattern, got {:?}", other.err()),
+        }
+    }
+}
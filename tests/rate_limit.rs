@@ -0,0 +1,178 @@
+use std::sync::Arc;
+
+use chrono::{TimeDelta, TimeZone, Utc};
+use logger::{warn, warn_limited, InMemoryWriter, LogConfig, LogLevel, Logger, ManualClock};
+
+fn limited_logger(per_second: u32) -> (Logger, InMemoryWriter, ManualClock) {
+    clocked_logger(LogConfig::new().with_rate_limit(per_second))
+}
+
+fn clocked_logger(config: LogConfig) -> (Logger, InMemoryWriter, ManualClock) {
+    let clock = ManualClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+    let config = config.with_clock(Arc::new(clock.clone()));
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    (logger, writer, clock)
+}
+
+fn lines(writer: &InMemoryWriter) -> Vec<String> {
+    writer.contents_string().lines().map(String::from).collect()
+}
+
+#[test]
+fn suppressed_records_are_reported_on_drop() {
+    let (logger, writer, _clock) = limited_logger(2);
+    for i in 0..10 {
+        warn!(logger, "busy {}", i);
+    }
+    drop(logger);
+
+    let lines = lines(&writer);
+    assert_eq!(lines.len(), 3, "{:?}", lines);
+    assert!(lines[0].contains("busy 0"));
+    assert!(lines[1].contains("busy 1"));
+    assert!(lines[2].contains("suppressed 8 similar messages"));
+}
+
+#[test]
+fn suppressed_records_are_reported_on_flush() {
+    let (logger, writer, _clock) = limited_logger(1);
+    for _ in 0..4 {
+        warn!(logger, "busy");
+    }
+    logger.flush();
+    assert!(lines(&writer)[1].contains("suppressed 3 similar messages"));
+
+    // Nothing is left to report afterwards.
+    drop(logger);
+    assert_eq!(lines(&writer).len(), 2);
+}
+
+#[test]
+fn closed_window_is_reported_by_another_call_site() {
+    let (logger, writer, clock) = limited_logger(1);
+    for _ in 0..3 {
+        warn!(logger, "first site");
+    }
+    clock.advance(TimeDelta::seconds(1));
+    warn!(logger, "second site");
+
+    let lines = lines(&writer);
+    assert_eq!(lines.len(), 3, "{:?}", lines);
+    assert!(lines[1].contains("suppressed 2 similar messages"));
+    assert!(lines[2].contains("second site"));
+}
+
+#[test]
+fn window_reopens_after_a_second() {
+    let (logger, writer, clock) = limited_logger(1);
+    for _ in 0..2 {
+        for _ in 0..3 {
+            warn!(logger, "busy");
+        }
+        clock.advance(TimeDelta::seconds(1));
+    }
+    drop(logger);
+
+    let lines = lines(&writer);
+    assert_eq!(lines.len(), 4, "{:?}", lines);
+    assert!(lines[1].contains("suppressed 2 similar messages"));
+    assert!(lines[2].contains("busy"));
+    assert!(lines[3].contains("suppressed 2 similar messages"));
+}
+
+// Checks and logs for its caller's file and line, as a wrapper would.
+#[track_caller]
+fn warn_if_enabled(logger: &Logger) -> bool {
+    let enabled = logger.enabled(LogLevel::Warn, None);
+    if enabled {
+        logger.warn("busy");
+    }
+    enabled
+}
+
+#[test]
+fn enabled_checks_the_call_sites_rate_limit() {
+    let (logger, writer, clock) = limited_logger(2);
+    // Checking alone uses up nothing.
+    for _ in 0..5 {
+        assert!(logger.enabled(LogLevel::Warn, None));
+    }
+    let checks: Vec<bool> = (0..4).map(|_| warn_if_enabled(&logger)).collect();
+    assert_eq!(checks, [true, true, false, false]);
+    // Other call sites have their own windows.
+    assert!(logger.enabled(LogLevel::Warn, None));
+
+    clock.advance(TimeDelta::seconds(1));
+    assert!(warn_if_enabled(&logger));
+    drop(logger);
+    assert_eq!(lines(&writer).len(), 3);
+}
+
+fn messages(lines: &[String]) -> Vec<&str> {
+    lines
+        .iter()
+        .map(|line| line.split_once("] ").unwrap().1)
+        .collect()
+}
+
+#[test]
+fn warn_limited_keeps_its_own_per_site_limit() {
+    let (logger, writer, clock) = clocked_logger(LogConfig::new());
+    for i in 0..10 {
+        warn_limited!(logger, 3 / sec, "retrying {}", i);
+    }
+    // Unlimited call sites are unaffected.
+    for i in 0..5 {
+        warn!(logger, "plain {}", i);
+    }
+    clock.advance(TimeDelta::seconds(1));
+    for i in 10..12 {
+        warn_limited!(logger, 3 / sec, "retrying {}", i);
+    }
+    drop(logger);
+
+    let lines = lines(&writer);
+    let messages = messages(&lines);
+    assert_eq!(
+        messages,
+        [
+            "retrying 0",
+            "retrying 1",
+            "retrying 2",
+            "plain 0",
+            "plain 1",
+            "plain 2",
+            "plain 3",
+            "plain 4",
+            "suppressed 7 similar messages in the last second",
+            "retrying 10",
+            "retrying 11",
+        ]
+    );
+    assert!(lines.iter().all(|line| line.contains(" WARN [")));
+}
+
+#[test]
+fn warn_limited_overrides_the_global_limit() {
+    let (logger, writer, _clock) = limited_logger(1);
+    for i in 0..8 {
+        warn_limited!(logger, 5 / sec, "limited {}", i);
+    }
+    for i in 0..4 {
+        warn!(logger, "global {}", i);
+    }
+    logger.flush();
+
+    let lines = lines(&writer);
+    let messages = messages(&lines);
+    let limited = messages
+        .iter()
+        .filter(|m| m.starts_with("limited "))
+        .count();
+    let global = messages.iter().filter(|m| m.starts_with("global ")).count();
+    assert_eq!(limited, 5, "{:?}", messages);
+    assert_eq!(global, 1, "{:?}", messages);
+    assert!(messages.contains(&"suppressed 3 similar messages in the last second"));
+    assert_eq!(messages.len(), 8, "{:?}", messages);
+}
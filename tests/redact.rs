@@ -0,0 +1,181 @@
+use std::fs;
+
+use logger::{ConfigError, InMemoryWriter, LogConfig, LogFormat, LogLevel, LogValue, Logger, Sink};
+
+const SECRET: &str = "hunter2";
+
+fn redacting(config: LogConfig) -> LogConfig {
+    config
+        .with_redact_patterns(&[r"hunter\d", r"tok_[a-z0-9]+"])
+        .unwrap()
+        .with_redact_keys(vec!["password".to_string(), "Authorization".to_string()])
+}
+
+fn logger(config: LogConfig) -> (Logger, InMemoryWriter) {
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    (logger, writer)
+}
+
+fn message_of(line: &str) -> &str {
+    line.split_once("] ").unwrap().1
+}
+
+#[test]
+fn overlapping_and_touching_matches_become_one_marker() {
+    let config = LogConfig::new()
+        .with_redact_patterns(&["abc", "bcd", "ef", "b"])
+        .unwrap();
+    let (logger, writer) = logger(config);
+    logger.info("x abcd y");
+    logger.info("x abcdef y");
+    logger.info("x abc ef y");
+    logger.info("nothing here");
+
+    let contents = writer.contents_string();
+    let messages: Vec<&str> = contents.lines().map(message_of).collect();
+    assert_eq!(
+        messages,
+        [
+            "x [REDACTED] y",
+            "x [REDACTED] y",
+            "x [REDACTED] [REDACTED] y",
+            "nothing here",
+        ]
+    );
+}
+
+#[test]
+fn a_replacement_does_not_create_new_matches() {
+    let config = LogConfig::new()
+        .with_redact_patterns(&["secret", r"\[RED"])
+        .unwrap();
+    let (logger, writer) = logger(config);
+    logger.info("a secret b");
+    assert_eq!(
+        message_of(writer.contents_string().trim_end()),
+        "a [REDACTED] b"
+    );
+}
+
+#[test]
+fn sensitive_keys_are_redacted_whatever_their_value() {
+    let (logger, writer) = logger(redacting(LogConfig::new()));
+    let fields: Vec<(&str, LogValue)> = vec![
+        ("PASSWORD", SECRET.into()),
+        ("authorization", 12345.into()),
+        ("user", "alice".into()),
+        ("note", "token tok_abc123 leaked".into()),
+    ];
+    logger.log_kv(LogLevel::Info, "login", &fields);
+
+    let line = writer.contents_string();
+    assert!(
+        line.ends_with(
+            "] login PASSWORD=[REDACTED] authorization=[REDACTED] user=alice \
+             note=\"token [REDACTED] leaked\"\n"
+        ),
+        "{}",
+        line
+    );
+}
+
+fn log_secrets(logger: &Logger) {
+    logger.warn(&format!("password={} rejected", SECRET));
+    logger.log_kv(
+        LogLevel::Error,
+        "auth",
+        &[("password", SECRET.into()), ("detail", SECRET.into())],
+    );
+    logger
+        .with_fields(&[("token", "tok_deadbeef".into())])
+        .info("bound");
+
+    #[derive(Debug)]
+    struct Leaky;
+    impl std::fmt::Display for Leaky {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "bad credentials {}", SECRET)
+        }
+    }
+    impl std::error::Error for Leaky {}
+    logger.error_chain(&Leaky);
+}
+
+#[test]
+fn secrets_never_reach_any_format() {
+    for format in [LogFormat::Text, LogFormat::Json, LogFormat::Logfmt] {
+        let (logger, writer) = logger(redacting(LogConfig::new().with_format(format)));
+        log_secrets(&logger);
+
+        let contents = writer.contents_string();
+        assert_eq!(contents.lines().count(), 4, "{:?}", format);
+        assert!(!contents.contains(SECRET), "{:?}: {}", format, contents);
+        assert!(!contents.contains("tok_"), "{:?}: {}", format, contents);
+        for line in contents.lines() {
+            assert!(line.contains("[REDACTED]"), "{:?}: {}", format, line);
+        }
+    }
+}
+
+#[test]
+fn sinks_only_see_redacted_lines() {
+    let sink = InMemoryWriter::new();
+    let config =
+        redacting(LogConfig::new()).add_sink(Sink::new(sink.clone()).with_format(LogFormat::Json));
+    let (logger, writer) = logger(config);
+    log_secrets(&logger);
+
+    for contents in [writer.contents_string(), sink.contents_string()] {
+        assert_eq!(contents.lines().count(), 4);
+        assert!(!contents.contains(SECRET), "{}", contents);
+    }
+}
+
+#[test]
+fn bad_patterns_fail_when_the_config_is_loaded() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    fs::write(
+        &path,
+        r#"{ "redact_patterns": ["hunter\\d", "(unclosed"] }"#,
+    )
+    .unwrap();
+    match LogConfig::from_json_file(&path) {
+        Err(e @ ConfigError::OutOfRange { .. }) => assert!(
+            e.to_string().starts_with(
+                "Invalid value for redact_patterns: invalid redact pattern \"(unclosed\""
+            ),
+            "{}",
+            e
+        ),
+        other => panic!("expected OutOfRange, got {:?}", other),
+    }
+
+    fs::write(
+        &path,
+        r#"{ "redact_patterns": ["hunter\\d"], "redact_keys": ["password"] }"#,
+    )
+    .unwrap();
+    let loaded = LogConfig::from_json_file(&path).unwrap();
+    assert_eq!(
+        loaded,
+        LogConfig::new()
+            .with_redact_patterns(&[r"hunter\d"])
+            .unwrap()
+            .with_redact_keys(vec!["password".to_string()])
+    );
+}
+
+#[test]
+fn bad_patterns_fail_in_the_builder() {
+    match LogConfig::new().with_redact_patterns(&["fine", "(unclosed"]) {
+        Err(e @ ConfigError::InvalidRedactPattern { .. }) => assert!(
+            e.to_string()
+                .starts_with("Invalid redact pattern \"(unclosed\": "),
+            "{}",
+            e
+        ),
+        other => panic!("expected InvalidRedactPattern, got {:?}", other),
+    }
+}
@@ -0,0 +1,107 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{fs, thread};
+
+use chrono::TimeDelta;
+use common::{file_config, file_name, log_files, read};
+use logger::{Logger, ReopenMode};
+
+#[test]
+fn records_after_a_rename_land_in_a_fresh_file_at_the_same_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let logger = Logger::new(Some(config));
+    logger.info("before");
+    let path = logger.current_file_path().unwrap();
+    let rotated = dir.path().join("rotated.1");
+    fs::rename(&path, &rotated).unwrap();
+
+    // Until the reopen, the old handle still points at the renamed file.
+    logger.info("still old");
+    logger.reopen().unwrap();
+    logger.info("after");
+
+    assert_eq!(logger.current_file_path().unwrap(), path);
+    let old = read(&rotated);
+    let old: Vec<&str> = old.lines().collect();
+    assert_eq!(old.len(), 2);
+    assert!(old[0].ends_with("] before") && old[1].ends_with("] still old"));
+    let fresh = read(&path);
+    assert_eq!(fresh.lines().count(), 1, "{}", fresh);
+    assert!(fresh.ends_with("] after\n"), "{}", fresh);
+}
+
+#[test]
+fn a_reopen_without_a_rename_keeps_appending() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let logger = Logger::new(Some(config));
+    logger.info("one");
+    logger.reopen().unwrap();
+    logger.info("two");
+
+    let files = log_files(dir.path());
+    assert_eq!(files.len(), 1);
+    assert_eq!(read(&files[0]).lines().count(), 2);
+}
+
+#[test]
+fn new_file_mode_starts_a_timestamped_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, clock) = file_config(dir.path());
+    let logger = Logger::new(Some(config.with_reopen_mode(ReopenMode::NewFile)));
+    logger.info("first");
+    let first = logger.current_file_path().unwrap();
+    clock.advance(TimeDelta::seconds(1));
+    logger.reopen().unwrap();
+    logger.info("second");
+
+    let second = logger.current_file_path().unwrap();
+    assert_ne!(first, second);
+    assert!(file_name(&second).starts_with("App_2024-01-01T00-00-01-"));
+    assert!(read(&first).ends_with("] first\n"));
+    assert!(read(&second).ends_with("] second\n"));
+}
+
+#[test]
+fn records_written_during_reopens_are_whole_and_kept() {
+    const THREADS: usize = 4;
+    const PER_THREAD: usize = 500;
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let logger = Logger::new(Some(config));
+    let path = logger.current_file_path().unwrap();
+
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let logger = &logger;
+            scope.spawn(move || {
+                for i in 0..PER_THREAD {
+                    logger.info(&format!("{}:{}", t, i));
+                }
+            });
+        }
+        for n in 0..20 {
+            fs::rename(&path, dir.path().join(format!("rotated.{}", n))).unwrap();
+            logger.reopen().unwrap();
+        }
+    });
+
+    let mut lines = 0;
+    for entry in fs::read_dir(dir.path()).unwrap() {
+        let path = entry.unwrap().path();
+        if file_name(&path).starts_with('.') {
+            continue;
+        }
+        for line in read(&path).lines() {
+            let message = line.rsplit_once("] ").unwrap().1;
+            let (t, i) = message.split_once(':').unwrap();
+            assert!(t.parse::<usize>().unwrap() < THREADS, "{}", line);
+            assert!(i.parse::<usize>().unwrap() < PER_THREAD, "{}", line);
+            lines += 1;
+        }
+    }
+    assert_eq!(lines, THREADS * PER_THREAD);
+}
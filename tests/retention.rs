@@ -0,0 +1,100 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use chrono::TimeDelta;
+use common::{file_config, log_files};
+use logger::{Logger, RollingConfig, RollingTimeOptions};
+
+fn touch(dir: &Path, name: &str) {
+    fs::write(dir.join(name), "old\n").unwrap();
+}
+
+fn names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn rotation_removes_the_oldest_files() {
+    let dir = tempfile::tempdir().unwrap();
+    // Deliberately not created in order.
+    for name in [
+        "App_2023-06-01T00-00-00-000000000+0000.log",
+        "App_2023-01-01T00-00-00-000000000+0000.log",
+        "App_2023-03-01T00-00-00-000000000+0000.1.log",
+        "App_2023-03-01T00-00-00-000000000+0000.log",
+        "App_2023-12-31T23-00-00-000000000-0200.log",
+    ] {
+        touch(dir.path(), name);
+    }
+    let (config, clock) = file_config(dir.path());
+    let rolling = RollingConfig::new()
+        .with_time_threshold(RollingTimeOptions::Minutely)
+        .with_max_files(3);
+    let logger = Logger::new(Some(config.with_rolling_config(rolling)));
+    logger.info("first");
+    clock.advance(TimeDelta::seconds(61));
+    logger.info("second");
+    drop(logger);
+
+    // 2023-12-31T23:00-02:00 is 2024-01-01T01:00 UTC, newer than both new files.
+    assert_eq!(
+        names(dir.path()),
+        [
+            "App_2023-12-31T23-00-00-000000000-0200.log",
+            "App_2024-01-01T00-00-00-000000000+0000.log",
+            "App_2024-01-01T00-01-01-000000000+0000.log",
+        ]
+    );
+}
+
+#[test]
+fn files_not_matching_the_prefix_are_left_alone() {
+    let dir = tempfile::tempdir().unwrap();
+    let unrelated = [
+        "Other_2020-01-01T00-00-00-000000000+0000.log",
+        "App_notes.log",
+        "App_2020-01-01T00-00-00-000000000+0000.txt",
+        "README",
+    ];
+    for name in unrelated {
+        touch(dir.path(), name);
+    }
+    touch(dir.path(), "App_2020-01-01T00-00-00-000000000+0000.log");
+    let (config, clock) = file_config(dir.path());
+    let rolling = RollingConfig::new()
+        .with_time_threshold(RollingTimeOptions::Minutely)
+        .with_max_files(1);
+    let logger = Logger::new(Some(config.with_rolling_config(rolling)));
+    logger.info("first");
+    clock.advance(TimeDelta::seconds(61));
+    logger.info("second");
+    drop(logger);
+
+    let mut expected: Vec<String> = unrelated.iter().map(|name| name.to_string()).collect();
+    expected.push("App_2024-01-01T00-01-01-000000000+0000.log".to_string());
+    expected.sort();
+    assert_eq!(names(dir.path()), expected);
+}
+
+#[test]
+fn nothing_is_removed_without_a_limit() {
+    let dir = tempfile::tempdir().unwrap();
+    touch(dir.path(), "App_2020-01-01T00-00-00-000000000+0000.log");
+    let (config, clock) = file_config(dir.path());
+    let rolling = RollingConfig::new().with_time_threshold(RollingTimeOptions::Minutely);
+    let logger = Logger::new(Some(config.with_rolling_config(rolling)));
+    for _ in 0..3 {
+        logger.info("tick");
+        clock.advance(TimeDelta::seconds(61));
+    }
+    drop(logger);
+
+    assert_eq!(log_files(dir.path()).len(), 4);
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use chrono::TimeDelta;
+use common::{file_config, log_files, read};
+use logger::{Logger, RollingConfig, RollingTimeOptions, Timezone};
+
+#[test]
+fn a_restart_appends_to_the_latest_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, clock) = file_config(dir.path());
+    let config = config.with_reuse_latest(true);
+    let first = Logger::new(Some(config.clone()));
+    first.info("first session");
+    drop(first);
+    clock.advance(TimeDelta::seconds(5));
+    let second = Logger::new(Some(config));
+    second.info("second session");
+    drop(second);
+
+    let files = log_files(dir.path());
+    assert_eq!(files.len(), 1);
+    let contents = read(&files[0]);
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("first session"));
+    assert!(lines[1].ends_with("second session"));
+}
+
+#[test]
+fn reuse_works_in_other_timezones() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, clock) = file_config(dir.path());
+    let config = config
+        .with_reuse_latest(true)
+        .with_timezone(Timezone::FixedOffset(5 * 3600))
+        .unwrap();
+    Logger::new(Some(config.clone())).info("first session");
+    clock.advance(TimeDelta::minutes(30));
+    Logger::new(Some(config)).info("second session");
+
+    assert_eq!(log_files(dir.path()).len(), 1);
+}
+
+#[test]
+fn size_accounting_continues_from_the_existing_length() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let config = config
+        .with_reuse_latest(true)
+        .with_rolling_config(RollingConfig::new().with_size_bytes(250));
+    let message = "x".repeat(60);
+    let first = Logger::new(Some(config.clone()));
+    first.info(&message);
+    first.info(&message);
+    drop(first);
+    // Two records of about 110 bytes fit in 250 bytes, a third doesn't.
+    let second = Logger::new(Some(config));
+    second.info(&message);
+    drop(second);
+
+    let files = log_files(dir.path());
+    assert_eq!(files.len(), 2);
+    assert_eq!(read(&files[0]).lines().count(), 2);
+    assert_eq!(read(&files[1]).lines().count(), 1);
+}
+
+#[test]
+fn an_expired_file_is_not_reused() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, clock) = file_config(dir.path());
+    let config = config
+        .with_reuse_latest(true)
+        .with_rolling_config(RollingConfig::new().with_time_threshold(RollingTimeOptions::Hourly));
+    Logger::new(Some(config.clone())).info("first session");
+    clock.advance(TimeDelta::hours(1));
+    Logger::new(Some(config)).info("second session");
+
+    assert_eq!(log_files(dir.path()).len(), 2);
+}
+
+#[test]
+fn without_the_option_every_start_gets_a_new_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, clock) = file_config(dir.path());
+    Logger::new(Some(config.clone())).info("first session");
+    clock.advance(TimeDelta::seconds(1));
+    Logger::new(Some(config)).info("second session");
+
+    assert_eq!(log_files(dir.path()).len(), 2);
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use std::fs;
+
+use chrono::TimeDelta;
+use common::{file_config, log_files, read};
+use logger::{Logger, RollingConfig, RollingSizeOptions, RollingTimeOptions};
+
+#[test]
+fn writing_past_the_size_threshold_starts_a_new_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let config = config
+        .with_rolling_config(RollingConfig::new().with_size_threshold(RollingSizeOptions::OneKB));
+    let logger = Logger::new(Some(config));
+    let message = "x".repeat(90);
+    for _ in 0..10 {
+        logger.info(&message);
+    }
+    let written = logger.stats().bytes_written();
+    drop(logger);
+
+    let files = log_files(dir.path());
+    assert_eq!(files.len(), 2);
+    let sizes: Vec<u64> = files
+        .iter()
+        .map(|file| fs::metadata(file).unwrap().len())
+        .collect();
+    let line_len = fs::read_to_string(&files[0])
+        .unwrap()
+        .lines()
+        .next()
+        .unwrap()
+        .len() as u64
+        + 1;
+    // The first file is as full as it gets without splitting a record.
+    let per_file = 1024 / line_len;
+    assert_eq!(sizes[0], per_file * line_len);
+    assert_eq!(sizes[1], (10 - per_file) * line_len);
+    assert_eq!(sizes[0] + sizes[1], written);
+}
+
+#[test]
+fn records_are_never_split_across_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let config = config.with_rolling_config(RollingConfig::new().with_size_bytes(100));
+    let logger = Logger::new(Some(config));
+    // Bigger than the threshold on its own.
+    logger.info(&"y".repeat(300));
+    logger.info("small");
+    drop(logger);
+
+    let files = log_files(dir.path());
+    assert_eq!(files.len(), 2);
+    for file in &files {
+        let contents = fs::read_to_string(file).unwrap();
+        assert_eq!(contents.lines().count(), 1);
+        assert!(contents.ends_with('\n'));
+    }
+}
+
+#[test]
+fn time_threshold_rolls_once_it_has_passed() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, clock) = file_config(dir.path());
+    let config = config.with_rolling_config(
+        RollingConfig::new().with_time_threshold(RollingTimeOptions::Minutely),
+    );
+    let logger = Logger::new(Some(config));
+    logger.info("first");
+    clock.advance(TimeDelta::seconds(59));
+    logger.info("still first");
+    clock.advance(TimeDelta::seconds(1));
+    logger.info("second");
+    drop(logger);
+
+    let files = log_files(dir.path());
+    assert_eq!(files.len(), 2);
+    assert_eq!(read(&files[0]).lines().count(), 2);
+    assert!(read(&files[1]).contains("second"));
+}
+
+#[test]
+fn idle_gap_starts_a_single_new_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, clock) = file_config(dir.path());
+    let config = config.with_rolling_config(
+        RollingConfig::new().with_time_threshold(RollingTimeOptions::Minutely),
+    );
+    let logger = Logger::new(Some(config));
+    logger.info("before");
+    clock.advance(TimeDelta::hours(3));
+    logger.info("after");
+    logger.info("after again");
+    drop(logger);
+
+    let files = log_files(dir.path());
+    assert_eq!(files.len(), 2);
+    assert_eq!(read(&files[1]).lines().count(), 2);
+}
+
+#[test]
+fn rotations_within_one_second_get_sequence_numbers() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let config = config.with_rolling_config(RollingConfig::new().with_size_bytes(100));
+    let logger = Logger::new(Some(config));
+    // The clock never moves, so every file shares one timestamp.
+    for message in ["first", "second", "third"] {
+        logger.info(&format!("{} {}", message, "x".repeat(60)));
+    }
+    drop(logger);
+
+    let files = log_files(dir.path());
+    let names: Vec<String> = files.iter().map(|path| common::file_name(path)).collect();
+    assert_eq!(
+        names,
+        [
+            "App_2024-01-01T00-00-00-000000000+0000.log",
+            "App_2024-01-01T00-00-00-000000000+0000.1.log",
+            "App_2024-01-01T00-00-00-000000000+0000.2.log",
+        ]
+    );
+    for (file, message) in files.iter().zip(["first", "second", "third"]) {
+        let contents = read(file);
+        assert_eq!(contents.lines().count(), 1);
+        assert!(contents.contains(message));
+    }
+}
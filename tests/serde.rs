@@ -0,0 +1,111 @@
+use std::{path::PathBuf, time::Duration};
+
+use logger::{
+    FlushPolicy, LogConfig, LogFormat, LogLevel, RollingConfig, RollingSizeOptions,
+    RollingTimeOptions, TimeAlignment, Timezone,
+};
+use serde_json::json;
+
+fn custom_config() -> LogConfig {
+    LogConfig::new()
+        .with_level(LogLevel::Debug)
+        .with_file_prefix("Svc_".to_string())
+        .with_directory(PathBuf::from("/var/log/svc"))
+        .with_target_level("hyper", LogLevel::Warn)
+        .with_format(LogFormat::Json)
+        .with_flush_policy(FlushPolicy::Interval(Duration::from_millis(250)))
+        .with_timezone(Timezone::FixedOffset(3600))
+        .unwrap()
+        .with_rolling_config(
+            RollingConfig::new()
+                .with_size_threshold(RollingSizeOptions::TenMB)
+                .with_time_threshold(RollingTimeOptions::Daily)
+                .with_max_files(7)
+                .with_time_alignment(TimeAlignment::Aligned),
+        )
+}
+
+#[test]
+fn serialization_shape() {
+    let value = serde_json::to_value(custom_config()).unwrap();
+
+    assert_eq!(value["level"], "debug");
+    assert_eq!(value["file_prefix"], "Svc_");
+    assert_eq!(value["directory"], "/var/log/svc");
+    assert_eq!(value["target_levels"], json!({ "hyper": "warn" }));
+    assert_eq!(value["format"], "json");
+    assert_eq!(value["pattern"], "%t %l [%f:%L] %m");
+    assert_eq!(
+        value["rolling_config"],
+        json!({
+            "time_threshold": 86400,
+            "size_threshold": 10485760,
+            "max_files": 7,
+            "time_alignment": "aligned",
+        })
+    );
+    // Sinks and the clock are runtime objects, not configuration.
+    assert!(value.get("sinks").is_none());
+    assert!(value.get("clock").is_none());
+}
+
+#[test]
+fn levels_serialize_as_lowercase_names() {
+    for (level, name) in [
+        (LogLevel::Trace, "trace"),
+        (LogLevel::Critical, "critical"),
+        (LogLevel::Off, "off"),
+    ] {
+        assert_eq!(serde_json::to_value(level).unwrap(), name);
+        assert_eq!(
+            serde_json::from_value::<LogLevel>(json!(name)).unwrap(),
+            level
+        );
+    }
+}
+
+#[test]
+fn configs_round_trip() {
+    for config in [LogConfig::new(), custom_config()] {
+        let json = serde_json::to_string(&config).unwrap();
+        let parsed: LogConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, config);
+    }
+    let rolling = RollingConfig::new().with_size_bytes(250 * 1024 * 1024);
+    let json = serde_json::to_string(&rolling).unwrap();
+    assert_eq!(
+        serde_json::from_str::<RollingConfig>(&json).unwrap(),
+        rolling
+    );
+}
+
+#[test]
+fn missing_keys_keep_their_defaults() {
+    let parsed: LogConfig = serde_json::from_value(json!({ "level": "error" })).unwrap();
+    assert_eq!(parsed, LogConfig::new().with_level(LogLevel::Error));
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    for value in [
+        json!({ "levle": "info" }),
+        json!({ "rolling_config": { "max_size": 10 } }),
+    ] {
+        let error = serde_json::from_value::<LogConfig>(value.clone()).unwrap_err();
+        assert!(
+            error.to_string().starts_with("unknown field"),
+            "{}: {}",
+            value,
+            error
+        );
+    }
+}
+
+#[test]
+fn clones_are_independent() {
+    let base = custom_config();
+    let variant = base.clone().with_level(LogLevel::Trace);
+    assert_ne!(base, variant);
+    assert_eq!(base, custom_config());
+    assert_eq!(serde_json::to_value(&variant).unwrap()["level"], "trace");
+}
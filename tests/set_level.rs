@@ -0,0 +1,77 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+use logger::{InMemoryWriter, LogConfig, LogLevel, Logger};
+
+#[test]
+fn changes_apply_to_the_next_record() {
+    let writer = InMemoryWriter::new();
+    let config = LogConfig::new().with_level(LogLevel::Info);
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    let clone = logger.clone();
+
+    logger.debug("hidden");
+    clone.set_level(LogLevel::Debug);
+    assert_eq!(logger.level(), LogLevel::Debug);
+    logger.debug("shown");
+    logger.set_level(LogLevel::Error);
+    clone.warn("hidden again");
+    clone.error("still shown");
+
+    let contents = writer.contents_string();
+    let messages: Vec<&str> = contents
+        .lines()
+        .map(|line| line.rsplit_once("] ").unwrap().1)
+        .collect();
+    assert_eq!(messages, ["shown", "still shown"]);
+}
+
+#[test]
+fn target_levels_are_left_alone() {
+    let writer = InMemoryWriter::new();
+    let config = LogConfig::new()
+        .with_level(LogLevel::Info)
+        .with_target_level("db", LogLevel::Trace);
+    let logger = Logger::with_writer(Some(config), Box::new(writer));
+    logger.set_level(LogLevel::Off);
+
+    assert!(!logger.enabled(LogLevel::Critical, None));
+    assert!(logger.enabled_for(LogLevel::Trace, "db::pool"));
+}
+
+#[test]
+fn levels_can_change_while_other_threads_log() {
+    let writer = InMemoryWriter::new();
+    let config = LogConfig::new().with_level(LogLevel::Warn);
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    let done = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                while !done.load(Ordering::Relaxed) {
+                    logger.info("maybe");
+                    logger.warn("always");
+                }
+            });
+        }
+        for i in 0..1000 {
+            let level = if i % 2 == 0 {
+                LogLevel::Info
+            } else {
+                LogLevel::Warn
+            };
+            logger.set_level(level);
+        }
+        logger.set_level(LogLevel::Error);
+        done.store(true, Ordering::Relaxed);
+    });
+
+    // The last change sticks for every thread.
+    let before = writer.contents().len();
+    logger.warn("after");
+    assert_eq!(writer.contents().len(), before);
+    assert_eq!(logger.level(), LogLevel::Error);
+}
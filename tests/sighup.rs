@@ -0,0 +1,35 @@
+#![cfg(all(unix, feature = "sighup"))]
+
+mod common;
+
+use std::{
+    fs, thread,
+    time::{Duration, Instant},
+};
+
+use common::{file_config, read};
+use logger::Logger;
+
+// The only test in this binary, since it signals the whole process.
+#[test]
+fn sighup_reopens_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let logger = Logger::new(Some(config));
+    logger.install_sighup_handler().unwrap();
+    logger.info("before");
+    let path = logger.current_file_path().unwrap();
+    fs::rename(&path, dir.path().join("rotated")).unwrap();
+
+    // SAFETY: raise has no preconditions, and SIGHUP is handled.
+    assert_eq!(unsafe { libc::raise(libc::SIGHUP) }, 0);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !path.exists() {
+        assert!(Instant::now() < deadline, "the file was never reopened");
+        thread::sleep(Duration::from_millis(10));
+    }
+    logger.info("after");
+
+    assert!(read(&path).ends_with("] after\n"));
+    assert!(read(&dir.path().join("rotated")).ends_with("] before\n"));
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use std::io::{self, Write};
+
+use common::{file_config, log_files};
+use logger::{InMemoryWriter, LogConfig, LogFormat, LogLevel, Logger, RollingConfig, Sink};
+
+fn log_one_of_each(logger: &Logger) {
+    logger.trace("trace");
+    logger.debug("debug");
+    logger.info("info");
+    logger.warn("warn");
+    logger.error("error");
+    logger.critical("critical");
+}
+
+fn levels(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(|line| {
+            let record: serde_json::Value = serde_json::from_str(line).unwrap();
+            record["level"].as_str().unwrap().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn each_sink_gets_the_records_at_or_above_its_level() {
+    let everything = InMemoryWriter::new();
+    let warnings = InMemoryWriter::new();
+    let main = InMemoryWriter::new();
+    let config = LogConfig::new()
+        .with_level(LogLevel::Trace)
+        .add_sink(Sink::new(everything.clone()).with_format(LogFormat::Json))
+        .add_sink(Sink::new(warnings.clone()).with_min_level(LogLevel::Warn));
+    let logger = Logger::with_writer(Some(config), Box::new(main.clone()));
+    log_one_of_each(&logger);
+
+    assert_eq!(
+        levels(&everything.contents_string()),
+        ["TRACE", "DEBUG", "INFO", "WARN", "ERROR", "CRITICAL"]
+    );
+    let warnings = warnings.contents_string();
+    let lines: Vec<&str> = warnings.lines().collect();
+    assert_eq!(lines.len(), 3);
+    // Without a format of its own the sink takes the logger's text format.
+    for (line, level) in lines.iter().zip(["WARN", "ERROR", "CRITICAL"]) {
+        assert!(line.contains(&format!("Z {} [", level)), "{}", line);
+    }
+    assert_eq!(main.contents_string().lines().count(), 6);
+}
+
+#[test]
+fn the_logger_level_applies_before_any_sink() {
+    let sink = InMemoryWriter::new();
+    let config = LogConfig::new()
+        .with_level(LogLevel::Error)
+        .add_sink(Sink::new(sink.clone()).with_format(LogFormat::Json));
+    let logger = Logger::with_writer(Some(config), Box::new(io::sink()));
+    log_one_of_each(&logger);
+
+    assert_eq!(levels(&sink.contents_string()), ["ERROR", "CRITICAL"]);
+}
+
+struct Broken;
+
+impl Write for Broken {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::Error::other("disk on fire"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn a_failing_sink_does_not_stop_the_others() {
+    let after = InMemoryWriter::new();
+    let main = InMemoryWriter::new();
+    let config = LogConfig::new()
+        .add_sink(Sink::new(Broken))
+        .add_sink(Sink::new(after.clone()));
+    let logger = Logger::with_writer(Some(config), Box::new(main.clone()));
+    logger.info("one");
+    logger.error("two");
+
+    assert_eq!(after.contents_string().lines().count(), 2);
+    assert_eq!(main.contents_string().lines().count(), 2);
+}
+
+#[test]
+fn rotation_only_applies_to_the_main_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let sink = InMemoryWriter::new();
+    let config = config
+        .with_rolling_config(RollingConfig::new().with_size_bytes(200))
+        .add_sink(Sink::new(sink.clone()).with_format(LogFormat::Json));
+    let logger = Logger::new(Some(config));
+    for i in 0..20 {
+        logger.info(&format!("record {}", i));
+    }
+
+    assert!(log_files(dir.path()).len() > 1);
+    assert_eq!(sink.contents_string().lines().count(), 20);
+}
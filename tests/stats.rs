@@ -0,0 +1,83 @@
+use std::io::{self, Write};
+
+use logger::{InMemoryWriter, LogConfig, LogLevel, Logger};
+
+const MIXTURE: [(LogLevel, usize); 6] = [
+    (LogLevel::Trace, 7),
+    (LogLevel::Debug, 5),
+    (LogLevel::Info, 4),
+    (LogLevel::Warn, 3),
+    (LogLevel::Error, 2),
+    (LogLevel::Critical, 1),
+];
+
+fn log_mixture(logger: &Logger) {
+    for (level, count) in MIXTURE {
+        for _ in 0..count {
+            logger.log(level, "counted");
+        }
+    }
+}
+
+#[test]
+fn counts_are_exact() {
+    let writer = InMemoryWriter::new();
+    let config = LogConfig::new().with_level(LogLevel::Info);
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    log_mixture(&logger);
+    let stats = logger.stats();
+
+    for (level, count) in MIXTURE {
+        let count = count as u64;
+        if level >= LogLevel::Info {
+            assert_eq!(stats.written(level), count, "{}", level);
+            assert_eq!(stats.filtered(level), 0, "{}", level);
+        } else {
+            assert_eq!(stats.written(level), 0, "{}", level);
+            assert_eq!(stats.filtered(level), count, "{}", level);
+        }
+    }
+    assert_eq!(stats.total_written(), 10);
+    assert_eq!(stats.bytes_written(), writer.contents().len() as u64);
+    assert_eq!(stats.write_errors(), 0);
+}
+
+#[test]
+fn handles_share_the_counters() {
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(None, Box::new(writer));
+    let child = logger.with_fields(&[("child", true.into())]);
+    logger.info("parent");
+    child.info("child");
+    child.debug("filtered");
+
+    assert_eq!(logger.stats().written(LogLevel::Info), 2);
+    assert_eq!(logger.stats().filtered(LogLevel::Debug), 1);
+    assert_eq!(child.stats().total_written(), 2);
+}
+
+struct Broken;
+
+impl Write for Broken {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::other("broken"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn failed_writes_are_counted_as_errors() {
+    let logger = Logger::with_writer(None, Box::new(Broken));
+    logger.info("lost");
+    logger.warn("also lost");
+    let stats = logger.stats();
+
+    // The failure starts a backoff, so the second record goes straight to the
+    // stderr fallback without another attempt.
+    assert_eq!(stats.write_errors(), 1);
+    assert_eq!(stats.fallback_writes(), 2);
+    assert_eq!(stats.bytes_written(), 0);
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::{file_config, log_files, read};
+use logger::{FlushPolicy, Logger, RollingConfig, SyncMode};
+
+#[test]
+fn every_mode_keeps_the_data() {
+    for mode in [SyncMode::None, SyncMode::OnFlush, SyncMode::EveryRecord] {
+        let dir = tempfile::tempdir().unwrap();
+        let (config, _clock) = file_config(dir.path());
+        let config = config
+            .with_sync_mode(mode)
+            .with_flush_policy(FlushPolicy::EveryRecords(10))
+            .with_rolling_config(RollingConfig::new().with_size_bytes(300));
+        let logger = Logger::new(Some(config));
+        for i in 0..12 {
+            logger.info(&format!("record {}", i));
+        }
+        logger.flush();
+        logger.info("after flush");
+        drop(logger);
+
+        let files = log_files(dir.path());
+        assert!(files.len() > 1, "{:?} didn't rotate", mode);
+        let contents: String = files.iter().map(|file| read(file)).collect();
+        assert_eq!(contents.lines().count(), 13, "{:?}", mode);
+        assert!(contents.ends_with("after flush\n"), "{:?}", mode);
+    }
+}
+
+#[test]
+fn every_record_writes_through_regardless_of_flush_policy() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let config = config
+        .with_sync_mode(SyncMode::EveryRecord)
+        .with_flush_policy(FlushPolicy::EveryRecords(100));
+    let logger = Logger::new(Some(config));
+    logger.info("synced");
+
+    assert!(read(&logger.current_file_path().unwrap()).ends_with("synced\n"));
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use std::{net::UdpSocket, process, sync::Arc, time::Duration};
+
+use logger::{
+    Facility, InMemoryWriter, LogConfig, LogLevel, LogValue, Logger, ManualClock, Sink,
+    SyslogConfig, SyslogTransport,
+};
+use regex::Regex;
+
+// `<PRI>VERSION TIMESTAMP HOSTNAME APP-NAME PROCID MSGID SD MSG` per RFC 5424,
+// with SD either "-" or one element.
+const RFC_5424: &str =
+    r#"^<(\d{1,3})>1 (\S+) (\S+) (\S+) (\S+) (\S+) (-|\[(?:[^"\]]|"(?:[^"\\]|\\.)*")*\]) (.*)$"#;
+
+struct Message {
+    priority: u8,
+    timestamp: String,
+    hostname: String,
+    app_name: String,
+    proc_id: String,
+    msg_id: String,
+    structured_data: String,
+    message: String,
+}
+
+fn parse(datagram: &[u8]) -> Message {
+    let text = std::str::from_utf8(datagram).unwrap();
+    let captures = Regex::new(RFC_5424)
+        .unwrap()
+        .captures(text)
+        .unwrap_or_else(|| panic!("not RFC 5424: {:?}", text));
+    Message {
+        priority: captures[1].parse().unwrap(),
+        timestamp: captures[2].to_string(),
+        hostname: captures[3].to_string(),
+        app_name: captures[4].to_string(),
+        proc_id: captures[5].to_string(),
+        msg_id: captures[6].to_string(),
+        structured_data: captures[7].to_string(),
+        message: captures[8].to_string(),
+    }
+}
+
+fn server() -> UdpSocket {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    socket
+}
+
+fn receive(socket: &UdpSocket) -> Message {
+    let mut buf = [0u8; 4096];
+    let (len, _) = socket.recv_from(&mut buf).unwrap();
+    parse(&buf[..len])
+}
+
+fn udp_logger(socket: &UdpSocket, syslog: impl FnOnce(SyslogConfig) -> SyslogConfig) -> Logger {
+    let config = syslog(SyslogConfig::udp(socket.local_addr().unwrap()));
+    let config = LogConfig::new()
+        .with_level(LogLevel::Trace)
+        .with_instance_name("web-1".to_string())
+        .with_clock(Arc::new(ManualClock::new(common::start_time())))
+        .add_sink(Sink::syslog(config));
+    Logger::with_writer(Some(config), Box::new(InMemoryWriter::new()))
+}
+
+#[test]
+fn datagrams_parse_as_rfc_5424() {
+    let socket = server();
+    let logger = udp_logger(&socket, |config| {
+        config
+            .with_facility(Facility::Local3)
+            .with_app_name("billing".to_string())
+    });
+    logger.warn("disk almost full");
+
+    let message = receive(&socket);
+    // local3 (19) * 8 + warning (4)
+    assert_eq!(message.priority, 156);
+    assert_eq!(message.timestamp, "2024-01-01T00:00:00.000000Z");
+    assert_eq!(message.hostname, "web-1");
+    assert_eq!(message.app_name, "billing");
+    assert_eq!(message.proc_id, process::id().to_string());
+    assert_eq!(message.msg_id, "-");
+    assert_eq!(message.structured_data, "-");
+    assert_eq!(message.message, "disk almost full");
+}
+
+#[test]
+fn levels_map_to_syslog_severities() {
+    let socket = server();
+    let logger = udp_logger(&socket, |config| config);
+    logger.trace("t");
+    logger.debug("d");
+    logger.info("i");
+    logger.warn("w");
+    logger.error("e");
+    logger.critical("c");
+
+    // The user facility is 1, so each priority is 8 + severity.
+    let priorities: Vec<u8> = (0..6).map(|_| receive(&socket).priority).collect();
+    assert_eq!(priorities, [15, 15, 14, 12, 11, 10]);
+}
+
+#[test]
+fn fields_become_escaped_structured_data() {
+    let socket = server();
+    let logger = udp_logger(&socket, |config| config);
+    let fields: Vec<(&str, LogValue)> = vec![
+        ("user", "alice".into()),
+        ("quote", r#"say "hi" [x] \o/"#.into()),
+        ("bad key=", 3.into()),
+    ];
+    logger.log_kv(LogLevel::Info, "signed in", &fields);
+
+    let message = receive(&socket);
+    assert_eq!(
+        message.structured_data,
+        r#"[fields@32473 user="alice" quote="say \"hi\" [x\] \\o/" bad_key_="3"]"#
+    );
+    assert_eq!(message.message, "signed in");
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_sockets_work_too() {
+    use std::os::unix::net::UnixDatagram;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log.sock");
+    let socket = UnixDatagram::bind(&path).unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let config =
+        LogConfig::new().add_sink(Sink::syslog(SyslogConfig::new(SyslogTransport::Unix(path))));
+    let logger = Logger::with_writer(Some(config), Box::new(InMemoryWriter::new()));
+    logger.error("over a unix socket");
+
+    let mut buf = [0u8; 4096];
+    let len = socket.recv(&mut buf).unwrap();
+    let message = parse(&buf[..len]);
+    assert_eq!(message.priority, 11);
+    assert_eq!(message.message, "over a unix socket");
+}
+
+#[test]
+fn send_failures_are_counted_not_raised() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("nobody-listens.sock");
+    let main = InMemoryWriter::new();
+    let config = LogConfig::new().add_sink(Sink::syslog(SyslogConfig::new(SyslogTransport::Unix(
+        missing,
+    ))));
+    let logger = Logger::with_writer(Some(config), Box::new(main.clone()));
+    logger.info("one");
+    logger.info("two");
+
+    assert_eq!(logger.stats().write_errors(), 2);
+    assert_eq!(main.contents_string().lines().count(), 2);
+}
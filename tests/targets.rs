@@ -0,0 +1,97 @@
+use logger::{debug, info, warn, InMemoryWriter, LogConfig, LogLevel, Logger};
+
+fn logger(config: LogConfig) -> (Logger, InMemoryWriter) {
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    (logger, writer)
+}
+
+fn messages(writer: &InMemoryWriter) -> Vec<String> {
+    writer
+        .contents_string()
+        .lines()
+        .map(|line| line.rsplit_once("] ").unwrap().1.to_string())
+        .collect()
+}
+
+#[test]
+fn the_longest_prefix_wins() {
+    let config = LogConfig::new()
+        .with_level(LogLevel::Info)
+        .with_target_level("hyper", LogLevel::Warn)
+        .with_target_level("hyper::client", LogLevel::Debug)
+        .with_target_level("hyper::client::pool", LogLevel::Error);
+    let (logger, _writer) = logger(config);
+
+    assert!(!logger.enabled_for(LogLevel::Info, "hyper"));
+    assert!(!logger.enabled_for(LogLevel::Info, "hyper::server"));
+    assert!(logger.enabled_for(LogLevel::Debug, "hyper::client"));
+    assert!(logger.enabled_for(LogLevel::Debug, "hyper::client::conn"));
+    assert!(!logger.enabled_for(LogLevel::Warn, "hyper::client::pool"));
+    assert!(logger.enabled_for(LogLevel::Error, "hyper::client::pool::idle"));
+}
+
+#[test]
+fn prefixes_only_match_whole_path_segments() {
+    let config = LogConfig::new()
+        .with_level(LogLevel::Info)
+        .with_target_level("hyper", LogLevel::Error);
+    let (logger, _writer) = logger(config);
+
+    assert!(logger.enabled_for(LogLevel::Info, "hyper_util"));
+    assert!(logger.enabled_for(LogLevel::Info, "hyperx::client"));
+    assert!(logger.enabled_for(LogLevel::Info, "app::hyper"));
+    assert!(!logger.enabled_for(LogLevel::Info, "hyper::client"));
+}
+
+#[test]
+fn overrides_beat_the_global_level_in_both_directions() {
+    let config = LogConfig::new()
+        .with_level(LogLevel::Info)
+        .with_target_level("noisy", LogLevel::Error)
+        .with_target_level("mine", LogLevel::Trace);
+    let (logger, writer) = logger(config);
+    warn!(target: "noisy::db", logger, "quieter");
+    info!(target: "other", logger, "global");
+    debug!(target: "other", logger, "below global");
+    debug!(target: "mine::core", logger, "louder");
+
+    assert_eq!(messages(&writer), ["global", "louder"]);
+    assert_eq!(logger.stats().filtered(LogLevel::Warn), 1);
+    assert_eq!(logger.stats().filtered(LogLevel::Debug), 1);
+}
+
+#[test]
+fn macros_default_to_the_module_path() {
+    // Integration tests are their own crate, named after the file.
+    assert_eq!(module_path!(), "targets");
+    let config = LogConfig::new()
+        .with_level(LogLevel::Info)
+        .with_target_level("targets", LogLevel::Error)
+        .with_target_level("targets::inner", LogLevel::Debug);
+    let (logger, writer) = logger(config);
+    warn!(logger, "from the crate root");
+    inner::log_debug(&logger);
+
+    assert_eq!(messages(&writer), ["from inner"]);
+}
+
+#[test]
+fn methods_without_a_target_use_the_global_level() {
+    let config = LogConfig::new()
+        .with_level(LogLevel::Info)
+        .with_target_level("targets", LogLevel::Error);
+    let (logger, writer) = logger(config);
+    logger.info("no target");
+    logger.log_target(LogLevel::Info, "targets", "explicit target", &[]);
+
+    assert_eq!(messages(&writer), ["no target"]);
+}
+
+mod inner {
+    use logger::{debug, Logger};
+
+    pub fn log_debug(logger: &Logger) {
+        debug!(logger, "from inner");
+    }
+}
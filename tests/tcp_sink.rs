@@ -0,0 +1,124 @@
+use std::{
+    io::{self, BufRead, BufReader},
+    net::{SocketAddr, TcpListener, TcpStream},
+    thread,
+    time::{Duration, Instant},
+};
+
+use logger::{InMemoryWriter, LogConfig, LogFormat, Logger, Sink};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn listen(addr: SocketAddr) -> TcpListener {
+    let listener = TcpListener::bind(addr).unwrap();
+    listener.set_nonblocking(true).unwrap();
+    listener
+}
+
+// Accepts with a deadline, so a sink that never connects fails the test
+// instead of hanging it.
+fn accept(listener: &TcpListener) -> BufReader<TcpStream> {
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false).unwrap();
+                stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+                return BufReader::new(stream);
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                assert!(Instant::now() < deadline, "the sink never connected");
+                thread::sleep(Duration::from_millis(10));
+            }
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+fn messages(reader: &mut BufReader<TcpStream>, count: usize) -> Vec<String> {
+    (0..count)
+        .map(|_| {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let record: serde_json::Value = serde_json::from_str(&line).unwrap();
+            record["message"].as_str().unwrap().to_string()
+        })
+        .collect()
+}
+
+fn tcp_logger(addr: SocketAddr, buffer_capacity: usize) -> Logger {
+    let sink = Sink::tcp(addr, buffer_capacity).with_format(LogFormat::Json);
+    let config = LogConfig::new().add_sink(sink);
+    Logger::with_writer(Some(config), Box::new(InMemoryWriter::new()))
+}
+
+#[test]
+fn records_arrive_as_json_lines() {
+    let listener = listen("127.0.0.1:0".parse().unwrap());
+    let logger = tcp_logger(listener.local_addr().unwrap(), 100);
+    logger.info("one");
+    logger.warn("two");
+    logger.error("three");
+
+    let mut reader = accept(&listener);
+    assert_eq!(messages(&mut reader, 3), ["one", "two", "three"]);
+}
+
+#[test]
+fn records_logged_while_disconnected_are_delivered_after_reconnecting() {
+    let listener = listen("127.0.0.1:0".parse().unwrap());
+    let addr = listener.local_addr().unwrap();
+    let logger = tcp_logger(addr, 100);
+    logger.info("before");
+    let mut reader = accept(&listener);
+    assert_eq!(messages(&mut reader, 1), ["before"]);
+
+    // Take the endpoint down entirely, log into the void, then bring it back.
+    drop(reader);
+    drop(listener);
+    for i in 0..5 {
+        logger.info(&format!("while down {}", i));
+    }
+    thread::sleep(Duration::from_millis(200));
+    let listener = listen(addr);
+
+    let mut reader = accept(&listener);
+    let expected: Vec<String> = (0..5).map(|i| format!("while down {}", i)).collect();
+    assert_eq!(messages(&mut reader, 5), expected);
+    assert_eq!(logger.stats().sink_dropped(), 0);
+
+    logger.info("after");
+    assert_eq!(messages(&mut reader, 1), ["after"]);
+}
+
+#[test]
+fn the_oldest_records_are_dropped_beyond_the_buffer() {
+    // Bound and released, so nothing is listening on the port.
+    let addr = listen("127.0.0.1:0".parse().unwrap()).local_addr().unwrap();
+    let logger = tcp_logger(addr, 3);
+
+    let started = Instant::now();
+    for i in 0..10 {
+        logger.info(&format!("record {}", i));
+    }
+    // A dead endpoint never holds up the caller.
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    let deadline = Instant::now() + TIMEOUT;
+    while logger.stats().sink_dropped() < 7 {
+        assert!(
+            Instant::now() < deadline,
+            "{}",
+            logger.stats().sink_dropped()
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let listener = listen(addr);
+    let mut reader = accept(&listener);
+    assert_eq!(
+        messages(&mut reader, 3),
+        ["record 7", "record 8", "record 9"]
+    );
+    assert_eq!(logger.stats().sink_dropped(), 7);
+}
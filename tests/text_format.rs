@@ -0,0 +1,60 @@
+mod common;
+
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use common::start_time;
+use logger::{InMemoryWriter, LogConfig, Logger, ManualClock, TimestampPrecision};
+
+fn text_logger(config: LogConfig) -> (Logger, InMemoryWriter) {
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    (logger, writer)
+}
+
+fn first_line(writer: &InMemoryWriter) -> String {
+    writer.contents_string().lines().next().unwrap().to_string()
+}
+
+#[test]
+fn timestamp_parses_back_at_every_precision() {
+    let now = start_time() + TimeDelta::nanoseconds(123_456_789);
+    for (precision, kept) in [
+        (TimestampPrecision::Secs, start_time()),
+        (
+            TimestampPrecision::Millis,
+            start_time() + TimeDelta::milliseconds(123),
+        ),
+        (
+            TimestampPrecision::Micros,
+            start_time() + TimeDelta::microseconds(123_456),
+        ),
+        (TimestampPrecision::Nanos, now),
+    ] {
+        let config = LogConfig::new()
+            .with_timestamp_precision(precision)
+            .with_clock(Arc::new(ManualClock::new(now)));
+        let (logger, writer) = text_logger(config);
+        logger.info("hello");
+
+        let line = first_line(&writer);
+        let timestamp = line.split(' ').next().unwrap();
+        let parsed: DateTime<Utc> = DateTime::parse_from_rfc3339(timestamp).unwrap().into();
+        assert_eq!(parsed, kept, "{:?}: {}", precision, line);
+    }
+}
+
+#[test]
+fn timestamp_comes_first_on_every_line() {
+    let (logger, writer) = text_logger(LogConfig::new());
+    let before = Utc::now();
+    logger.info("one");
+    logger.warn("two");
+    let after = Utc::now();
+
+    for line in writer.contents_string().lines() {
+        let timestamp = line.split(' ').next().unwrap();
+        let parsed: DateTime<Utc> = DateTime::parse_from_rfc3339(timestamp).unwrap().into();
+        assert!(parsed >= before - TimeDelta::milliseconds(1) && parsed <= after);
+    }
+}
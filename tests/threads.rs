@@ -0,0 +1,145 @@
+mod common;
+
+use std::{collections::HashSet, sync::Arc, thread};
+
+use common::{file_config, log_files, read};
+use logger::{InMemoryWriter, LogConfig, LogFormat, Logger};
+use regex::Regex;
+
+#[test]
+fn concurrent_records_land_whole() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let logger = Arc::new(Logger::new(Some(config)));
+    let threads: Vec<_> = (0..8)
+        .map(|t| {
+            let logger = Arc::clone(&logger);
+            thread::spawn(move || {
+                for i in 0..1000 {
+                    logger.info(&format!("thread {} record {}", t, i));
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    drop(logger);
+
+    let files = log_files(dir.path());
+    assert_eq!(files.len(), 1);
+    let contents = read(&files[0]);
+    let line =
+        Regex::new(r"^\S+ INFO \[tests/threads\.rs:\d+\] thread (\d) record (\d+)$").unwrap();
+    let mut seen = HashSet::new();
+    for text in contents.lines() {
+        let captures = line
+            .captures(text)
+            .unwrap_or_else(|| panic!("bad line {:?}", text));
+        assert!(seen.insert((captures[1].to_string(), captures[2].to_string())));
+    }
+    assert_eq!(seen.len(), 8000);
+}
+
+#[test]
+fn clones_share_one_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _clock) = file_config(dir.path());
+    let logger = Logger::new(Some(config));
+    let clone = logger.clone();
+    thread::spawn(move || clone.info("from the clone"))
+        .join()
+        .unwrap();
+    logger.info("from the original");
+    drop(logger);
+
+    let files = log_files(dir.path());
+    assert_eq!(files.len(), 1);
+    assert_eq!(read(&files[0]).lines().count(), 2);
+}
+
+fn thread_info_logger(format: LogFormat) -> (Logger, InMemoryWriter) {
+    let config = LogConfig::new().with_thread_info(true).with_format(format);
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    (logger, writer)
+}
+
+#[test]
+fn named_threads_appear_on_their_lines() {
+    let (logger, writer) = thread_info_logger(LogFormat::Text);
+    thread::scope(|scope| {
+        for name in ["tokio-runtime-worker-3", "db-pool"] {
+            thread::Builder::new()
+                .name(name.to_string())
+                .spawn_scoped(scope, {
+                    let logger = &logger;
+                    move || logger.info(&format!("from {}", name))
+                })
+                .unwrap();
+        }
+    });
+
+    let contents = writer.contents_string();
+    assert_eq!(contents.lines().count(), 2);
+    for line in contents.lines() {
+        let name = line.rsplit_once("from ").unwrap().1;
+        assert!(line.contains(&format!(" INFO [{}] [", name)), "{}", line);
+    }
+}
+
+#[test]
+fn unnamed_threads_get_a_stable_number() {
+    let (logger, writer) = thread_info_logger(LogFormat::Text);
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            logger.info("first");
+            logger.info("second");
+        });
+    });
+
+    let contents = writer.contents_string();
+    let threads: Vec<&str> = contents
+        .lines()
+        .map(|line| line.split(' ').nth(2).unwrap())
+        .collect();
+    assert_eq!(threads[0], threads[1]);
+    let number = threads[0].trim_matches(&['[', ']'][..]);
+    assert!(number.parse::<u64>().is_ok(), "{}", number);
+}
+
+#[test]
+fn json_and_logfmt_get_separate_fields() {
+    let (json_logger, json) = thread_info_logger(LogFormat::Json);
+    let (logfmt_logger, logfmt) = thread_info_logger(LogFormat::Logfmt);
+    thread::Builder::new()
+        .name("worker".to_string())
+        .spawn(move || {
+            json_logger.info("json");
+            logfmt_logger.info("logfmt");
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    let record: serde_json::Value =
+        serde_json::from_str(json.contents_string().trim_end()).unwrap();
+    assert_eq!(record["thread_name"], "worker");
+    assert!(record["thread_id"].is_u64());
+    let line = logfmt.contents_string();
+    assert!(line.contains(" thread_name=worker thread_id="), "{}", line);
+}
+
+#[test]
+fn nothing_is_added_by_default() {
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(None, Box::new(writer.clone()));
+    thread::Builder::new()
+        .name("quiet".to_string())
+        .spawn(move || logger.info("plain"))
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert!(!writer.contents_string().contains("quiet"));
+}
@@ -0,0 +1,170 @@
+use std::{fs, path::Path, sync::Arc};
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use logger::{
+    InMemoryWriter, LogConfig, LogFormat, Logger, ManualClock, RollingConfig, RollingTimeOptions,
+    TimeAlignment, Timezone,
+};
+
+const ONE_HOUR_EAST: Timezone = Timezone::FixedOffset(3600);
+
+fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, hour, minute, 0).unwrap()
+}
+
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".log"))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn record_timestamps_carry_the_offset() {
+    let clock = ManualClock::new(at(23, 30));
+    let config = LogConfig::new()
+        .with_timezone(ONE_HOUR_EAST)
+        .unwrap()
+        .with_clock(Arc::new(clock));
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    logger.info("late");
+
+    let line = writer.contents_string();
+    assert!(
+        line.starts_with("2024-01-02T00:30:00.000+01:00"),
+        "{}",
+        line
+    );
+    let timestamp = line.split_whitespace().next().unwrap();
+    let parsed = DateTime::parse_from_rfc3339(timestamp.trim_matches(&['[', ']'][..])).unwrap();
+    assert_eq!(parsed, at(23, 30));
+}
+
+#[test]
+fn file_names_carry_the_offset() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = LogConfig::new()
+        .with_directory(dir.path().to_path_buf())
+        .with_file_prefix("App_".to_string())
+        .with_timezone(ONE_HOUR_EAST)
+        .unwrap()
+        .with_clock(Arc::new(ManualClock::new(at(23, 30))));
+    let logger = Logger::new(Some(config));
+    logger.info("hello");
+    drop(logger);
+
+    assert_eq!(
+        file_names(dir.path()),
+        ["App_2024-01-02T00-30-00-000000000+0100.log"]
+    );
+}
+
+#[test]
+fn daily_boundary_follows_the_timezone() {
+    let dir = tempfile::tempdir().unwrap();
+    let clock = ManualClock::new(at(22, 30));
+    let config = LogConfig::new()
+        .with_directory(dir.path().to_path_buf())
+        .with_file_prefix("App_".to_string())
+        .with_timezone(ONE_HOUR_EAST)
+        .unwrap()
+        .with_rolling_config(
+            RollingConfig::new()
+                .with_time_threshold(RollingTimeOptions::Daily)
+                .with_time_alignment(TimeAlignment::Aligned),
+        )
+        .with_clock(Arc::new(clock.clone()));
+    let logger = Logger::new(Some(config));
+    logger.info("before local midnight");
+    // 23:30 UTC is still January 1st in UTC but not an hour east of it.
+    clock.advance(TimeDelta::hours(1));
+    logger.info("after local midnight");
+    drop(logger);
+
+    assert_eq!(
+        file_names(dir.path()),
+        ["App_2024-01-01.log", "App_2024-01-02.log"]
+    );
+}
+
+#[test]
+fn json_config_accepts_serde_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("logger.json");
+    fs::write(
+        &path,
+        r#"{"level": "debug", "timezone": {"fixed_offset": 3600}, "format": "json"}"#,
+    )
+    .unwrap();
+    let config = LogConfig::from_json_file(&path).unwrap();
+
+    let expected = LogConfig::new()
+        .with_level(logger::LogLevel::Debug)
+        .with_timezone(ONE_HOUR_EAST)
+        .unwrap()
+        .with_format(LogFormat::Json);
+    assert_eq!(config, expected);
+}
+
+#[test]
+fn json_config_warns_about_unknown_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("logger.json");
+    fs::write(&path, r#"{"timezone": "local", "timezon": "utc"}"#).unwrap();
+    let config = LogConfig::from_json_file(&path).unwrap();
+
+    assert_eq!(config.warnings(), [r#"Unknown config key "timezon""#]);
+}
+
+#[test]
+fn json_config_names_the_bad_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("logger.json");
+    fs::write(&path, r#"{"timezone": "mars"}"#).unwrap();
+    let error = LogConfig::from_json_file(&path).unwrap_err();
+
+    assert!(
+        error.to_string().starts_with("Invalid value for timezone"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn offsets_of_a_day_or_more_are_rejected() {
+    for seconds in [86_400, -86_400, i32::MAX, i32::MIN] {
+        match LogConfig::new().with_timezone(Timezone::FixedOffset(seconds)) {
+            Err(e @ logger::ConfigError::OutOfRange { .. }) => assert_eq!(
+                e.to_string(),
+                format!(
+                    "Invalid value for timezone: offset {} must be less than a day (86400 \
+                     seconds) either way",
+                    seconds
+                )
+            ),
+            other => panic!("expected OutOfRange for {}, got {:?}", seconds, other),
+        }
+    }
+    assert!(LogConfig::new()
+        .with_timezone(Timezone::FixedOffset(-86_399))
+        .is_ok());
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("logger.json");
+    fs::write(&path, r#"{"timezone": {"fixed_offset": 90000}}"#).unwrap();
+    match LogConfig::from_json_file(&path) {
+        Err(e @ logger::ConfigError::OutOfRange { .. }) => assert!(
+            e.to_string()
+                .starts_with("Invalid value for timezone: offset 90000 must be less than a day"),
+            "{}",
+            e
+        ),
+        other => panic!("expected OutOfRange, got {:?}", other),
+    }
+    let serde = serde_json::from_str::<LogConfig>(r#"{"timezone": {"fixed_offset": -90000}}"#);
+    assert!(serde.is_err());
+}
@@ -0,0 +1,100 @@
+use logger::{InMemoryWriter, LogConfig, LogFormat, LogLevel, LogValue, Logger};
+
+fn logger(config: LogConfig) -> (Logger, InMemoryWriter) {
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    (logger, writer)
+}
+
+// Output that isn't valid UTF-8 fails here rather than being patched up by a
+// lossy conversion.
+fn lines(writer: &InMemoryWriter) -> Vec<String> {
+    String::from_utf8(writer.contents())
+        .expect("output is valid UTF-8")
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+fn message_of(line: &str) -> &str {
+    line.split_once("] ").unwrap().1
+}
+
+// Splits "<kept>…[truncated N bytes]" into the kept text and N.
+fn split_marker(message: &str) -> (&str, usize) {
+    let (kept, marker) = message.rsplit_once("…[truncated ").unwrap();
+    (
+        kept,
+        marker.strip_suffix(" bytes]").unwrap().parse().unwrap(),
+    )
+}
+
+#[test]
+fn cuts_that_straddle_a_character_back_off_to_its_start() {
+    // Two-, three- and four-byte characters after an ASCII prefix, so every
+    // cut point from inside the prefix to past the end gets tried.
+    for message in ["abééé", "ab€€€", "ab😀😀😀"] {
+        for max_len in 0..message.len() + 2 {
+            let (logger, writer) = logger(LogConfig::new().with_max_message_len(max_len));
+            logger.info(message);
+
+            let lines = lines(&writer);
+            let logged = message_of(&lines[0]);
+            if message.len() <= max_len {
+                assert_eq!(logged, message);
+                assert_eq!(logger.stats().truncated_bytes(), 0);
+                continue;
+            }
+            let (kept, removed) = split_marker(logged);
+            assert!(kept.len() <= max_len, "{:?} cut at {}", message, max_len);
+            assert!(max_len - kept.len() < 4, "{:?} cut at {}", message, max_len);
+            assert!(message.starts_with(kept));
+            assert_eq!(kept.len() + removed, message.len());
+            assert_eq!(logger.stats().truncated_bytes(), removed as u64);
+        }
+    }
+}
+
+#[test]
+fn messages_are_unlimited_by_default() {
+    let message = "é".repeat(100_000);
+    let (logger, writer) = logger(LogConfig::new());
+    logger.info(&message);
+    assert_eq!(message_of(&lines(&writer)[0]), message);
+    assert_eq!(logger.stats().truncated_bytes(), 0);
+}
+
+#[test]
+fn truncation_is_counted_across_records() {
+    let (logger, writer) = logger(LogConfig::new().with_max_message_len(10));
+    logger.info(&"x".repeat(12_034_577));
+    logger.info("short");
+    logger.info(&"y".repeat(15));
+
+    let lines = lines(&writer);
+    assert!(lines[0].ends_with("] xxxxxxxxxx…[truncated 12034567 bytes]"));
+    assert!(lines[1].ends_with("] short"));
+    assert_eq!(logger.stats().truncated_bytes(), 12_034_567 + 5);
+}
+
+#[test]
+fn string_fields_have_their_own_cap() {
+    let config = LogConfig::new()
+        .with_format(LogFormat::Json)
+        .with_max_message_len(100)
+        .with_max_field_len(5);
+    let (logger, writer) = logger(config);
+    let fields: Vec<(&str, LogValue)> = vec![
+        ("body", "日本語のテキスト".into()),
+        ("short", "ok".into()),
+        ("count", 1_234_567_890.into()),
+    ];
+    logger.log_kv(LogLevel::Info, "a message well under the cap", &fields);
+
+    let record: serde_json::Value = serde_json::from_str(&lines(&writer)[0]).unwrap();
+    assert_eq!(record["message"], "a message well under the cap");
+    assert_eq!(record["fields"]["body"], "日…[truncated 21 bytes]");
+    assert_eq!(record["fields"]["short"], "ok");
+    assert_eq!(record["fields"]["count"], 1_234_567_890);
+    assert_eq!(logger.stats().truncated_bytes(), 21);
+}
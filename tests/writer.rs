@@ -0,0 +1,34 @@
+mod common;
+
+use std::sync::Arc;
+
+use logger::{InMemoryWriter, LogConfig, Logger, ManualClock, RollingConfig};
+
+#[test]
+fn a_custom_writer_creates_no_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let logs = dir.path().join("logs");
+    let config = LogConfig::new()
+        .with_directory(logs.clone())
+        .with_rolling_config(RollingConfig::new().with_size_bytes(10))
+        .with_clock(Arc::new(ManualClock::new(common::start_time())));
+    let writer = InMemoryWriter::new();
+    let logger = Logger::with_writer(Some(config), Box::new(writer.clone()));
+    let line = line!() + 1;
+    logger.info("into memory");
+    logger.info("past the size threshold");
+
+    assert_eq!(logger.current_file_path(), None);
+    assert!(!logs.exists());
+    assert!(logger.take_error().is_none());
+    assert_eq!(
+        writer.contents_string(),
+        format!(
+            "2024-01-01T00:00:00.000Z INFO [{file}:{}] into memory\n\
+             2024-01-01T00:00:00.000Z INFO [{file}:{}] past the size threshold\n",
+            line,
+            line + 1,
+            file = file!()
+        )
+    );
+}